@@ -0,0 +1,194 @@
+use crate::history::{self, ChangeKind};
+use crate::{notes, tasks_file_manager, HistoryEntry, Task};
+use std::collections::HashMap;
+use std::path::Path;
+use std::process::Command;
+
+/// URLs are kept as they are, local files by their absolute path
+fn is_url(reference: &str) -> bool {
+    reference.contains("://")
+}
+
+/// Links the file or URL to the task, the file has to exist when it is attached
+pub fn attach(
+    tasks: &mut [Task],
+    tasks_history: &mut HashMap<String, Vec<HistoryEntry>>,
+    task_label_number: &str,
+    reference: &str,
+) -> Result<(), String> {
+    let reference = reference.trim();
+    // quotes are kept by the interactive prompt, e.g. attach 5 "./design docs/Spec.pdf"
+    let reference = reference
+        .strip_prefix('"')
+        .and_then(|reference| reference.strip_suffix('"'))
+        .unwrap_or(reference);
+
+    let reference = if is_url(reference) {
+        reference.to_string()
+    } else {
+        Path::new(reference)
+            .canonicalize()
+            .map_err(|err| format!("Could not attach {reference}: {err}"))?
+            .display()
+            .to_string()
+    };
+
+    let task = Task::find_by_label_number(tasks, task_label_number)?;
+
+    if task.attachments.contains(&reference) {
+        return Err(format!("{reference} is already attached to {}", task.label));
+    }
+
+    let prev_task = task.clone();
+    task.attachments.push(reference);
+
+    history::record(tasks_history, ChangeKind::Attach, prev_task, task);
+
+    println!("Attached to task:\n{task}{}", describe(task));
+
+    tasks_file_manager::save(tasks, tasks_history);
+
+    Ok(())
+}
+
+/// Opens attachment with given number, every attachment of the task if it is not given
+pub fn open(tasks: &[Task], task_label_number: &str, number: Option<&str>) -> Result<(), String> {
+    let task_labeled_by = format!("Task {task_label_number}");
+
+    let task = tasks
+        .iter()
+        .find(|x| x.label == task_labeled_by)
+        .ok_or("Could not found Task with this label".to_string())?;
+
+    if task.attachments.is_empty() {
+        return Err(format!("{task_labeled_by} has no attachments"));
+    }
+
+    let attachments = match number {
+        Some(number) => {
+            let attachment = number
+                .parse::<usize>()
+                .ok()
+                .and_then(|number| task.attachments.get(number.checked_sub(1)?))
+                .ok_or(format!(
+                    "{task_labeled_by} has no attachment {number}, attachments are numbered in show task {task_label_number}"
+                ))?;
+
+            vec![attachment]
+        }
+        None => task.attachments.iter().collect(),
+    };
+
+    for attachment in attachments {
+        launch(attachment)?;
+    }
+
+    Ok(())
+}
+
+/// $RUTODO_OPENER if set, otherwise the opener of the system
+fn launch(attachment: &str) -> Result<(), String> {
+    if !is_url(attachment) && !Path::new(attachment).exists() {
+        return Err(format!("{attachment} no longer exists"));
+    }
+
+    let opener = std::env::var("RUTODO_OPENER")
+        .ok()
+        .filter(|opener| !opener.trim().is_empty());
+
+    let mut command = match opener {
+        // opener can be given with arguments, e.g. "firefox --new-tab"
+        Some(opener) => {
+            let mut words = opener.split_whitespace().map(String::from);
+            let mut command = Command::new(words.next().unwrap_or_default());
+            command.args(words);
+            command
+        }
+        None if cfg!(windows) => {
+            let mut command = Command::new("cmd");
+            command.args(["/C", "start", ""]);
+            command
+        }
+        None if cfg!(target_os = "macos") => Command::new("open"),
+        None => Command::new("xdg-open"),
+    };
+
+    let program = command.get_program().to_string_lossy().to_string();
+
+    let status = command
+        .arg(attachment)
+        .status()
+        .map_err(|err| format!("Could not start {program}: {err}"))?;
+
+    if !status.success() {
+        return Err(format!(
+            "{program} exited with {status} when opening {attachment}"
+        ));
+    }
+
+    Ok(())
+}
+
+/// Reports attachments of every task which local files no longer exist, fails if there are any
+pub fn check(tasks: &[Task]) -> Result<(), String> {
+    let missing = tasks
+        .iter()
+        .flat_map(|task| {
+            task.attachments
+                .iter()
+                .filter(|attachment| !is_url(attachment) && !Path::new(attachment).exists())
+                .map(move |attachment| (&task.label, attachment))
+        })
+        .collect::<Vec<_>>();
+
+    if missing.is_empty() {
+        println!("All attached files exist");
+        return Ok(());
+    }
+
+    for (label, attachment) in &missing {
+        println!("{label}: {attachment} no longer exists");
+    }
+
+    Err(format!("{} attached files are missing", missing.len()))
+}
+
+/// Numbered attachments printed under the task, empty if the task has none
+pub fn describe(task: &Task) -> String {
+    if task.attachments.is_empty() {
+        return String::new();
+    }
+
+    let mut description = "Attachments:\n".to_string();
+
+    for (idx, attachment) in task.attachments.iter().enumerate() {
+        description.push_str(&format!("    {}. {attachment}\n", idx + 1));
+    }
+
+    description
+}
+
+// Attachments of the task line are separated by semicolons:
+// attachments: /home/alice/design.pdf;https%3A//example.com/spec
+pub fn format_field(task: &Task) -> String {
+    if task.attachments.is_empty() {
+        return String::new();
+    }
+
+    let attachments = task
+        .attachments
+        .iter()
+        .map(|attachment| notes::encode(attachment))
+        .collect::<Vec<_>>()
+        .join(";");
+
+    format!(", attachments: {attachments}")
+}
+
+pub fn parse_field(attachments: &str) -> Vec<String> {
+    attachments
+        .split(';')
+        .filter(|attachment| !attachment.trim().is_empty())
+        .map(|attachment| notes::decode(attachment.trim()))
+        .collect()
+}
@@ -0,0 +1,1150 @@
+use crate::cli_manager::{Switches, STATUS_NAMES};
+use crate::history::ChangeKind;
+use crate::Task;
+
+/// Arguments that switch takes, placeholder is displayed in the help message
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum Arguments {
+    Nothing,
+    Required(&'static str),
+    Optional(&'static str),
+}
+
+/// What can be completed in place of an argument
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum Values {
+    Nothing,
+    TaskIds,
+    TaskIdsOrAll,
+    Statuses,
+    Formats,
+    Dates,
+    Tags,
+    Files,
+    Shells,
+    Operations,
+    SortKeys,
+}
+
+pub struct SwitchSpec {
+    pub name: &'static str,
+    pub aliases: &'static [&'static str],
+    pub arguments: Arguments,
+    pub values: Values,
+    // switch can be prefixed with --not-
+    pub negatable: bool,
+    pub description: &'static str,
+    pub examples: &'static [&'static str],
+}
+
+pub struct CommandSpec {
+    pub name: &'static str,
+    pub aliases: &'static [&'static str],
+    pub usage: &'static str,
+    pub description: &'static str,
+    pub arguments: Values,
+    pub switches: &'static [SwitchSpec],
+    // filtering switches of show tasks select the tasks instead of the id
+    pub accepts_filters: bool,
+    pub notes: &'static [&'static str],
+}
+
+const fn switch(
+    name: &'static str,
+    arguments: Arguments,
+    values: Values,
+    description: &'static str,
+) -> SwitchSpec {
+    SwitchSpec {
+        name,
+        aliases: &[],
+        arguments,
+        values,
+        negatable: false,
+        description,
+        examples: &[],
+    }
+}
+
+const DATE_ARGUMENTS: &str = "[Filtering method] [Formatted date string -> format: 10/06/2023 12:30 | 10/06/2023 | tomorrow 12:30 | today 12:30 | next 12:30 | tomorrow | next | today | 12:30]";
+
+const FORMAT_SWITCH: SwitchSpec = switch(
+    "format",
+    Arguments::Required("<plain | json | jsonl | csv | tsv | table>"),
+    Values::Formats,
+    "print in given format, plain by default",
+);
+
+pub const SHOW_TASKS: CommandSpec = CommandSpec {
+    name: "show tasks",
+    aliases: &["1"],
+    usage: "show tasks | 1 [switch] [arguments]",
+    description: "Display every task, optionally filter or sort the output. Switches can be chained, after usage of switch, stream is redirected and next switch operate on previous output",
+    arguments: Values::Nothing,
+    switches: &[
+        SwitchSpec {
+            negatable: true,
+            ..switch(
+                "thing",
+                Arguments::Required("<String>"),
+                Values::Nothing,
+                "show tasks that starts with this string in thing field",
+            )
+        },
+        SwitchSpec {
+            negatable: true,
+            examples: &[
+                "--status postponed => match every task with status as postponed",
+                "--status todo,postponed => match every task with status as todo or postponed",
+            ],
+            ..switch(
+                "status",
+                Arguments::Required("<TaskStatus[,TaskStatus]>"),
+                Values::Statuses,
+                "show tasks with any of given statuses",
+            )
+        },
+        SwitchSpec {
+            negatable: true,
+            examples: &["--note vendor => match every task with vendor in its note or annotations"],
+            ..switch(
+                "note",
+                Arguments::Required("<String>"),
+                Values::Nothing,
+                "show tasks which note or annotations contain this string, case is ignored",
+            )
+        },
+        SwitchSpec {
+            negatable: true,
+            examples: &[
+                "--tag work => match every task with #work in thing field",
+            ],
+            ..switch(
+                "tag",
+                Arguments::Required("<Tag>"),
+                Values::Tags,
+                "show tasks tagged with given tag, tags are words starting with # in thing field",
+            )
+        },
+        SwitchSpec {
+            negatable: true,
+            examples: &[
+                "--deadline => sorts tasks by deadline from earliest date",
+                "--deadline tomorrow => show tasks with deadline that is tomorrow, ignoring hours:minutes",
+                "--deadline tomorrow 12:30 => show tasks with deadline as tomorrow 12:30",
+                "--deadline -le today 12:30 => show tasks with deadline earlier or equal to today 12:30",
+            ],
+            ..switch(
+                "deadline",
+                Arguments::Optional(DATE_ARGUMENTS),
+                Values::Dates,
+                "filter or sort tasks by deadline date, takes the same filtering methods as --date",
+            )
+        },
+        SwitchSpec {
+            negatable: true,
+            examples: &[
+                "--created -gt 01/10/2026 => show tasks created after the date",
+                "--created => sorts tasks by creation date from earliest date",
+            ],
+            ..switch(
+                "created",
+                Arguments::Optional(DATE_ARGUMENTS),
+                Values::Dates,
+                "filter or sort tasks by creation date, takes the same filtering methods as --date",
+            )
+        },
+        SwitchSpec {
+            negatable: true,
+            examples: &["--modified last-7-days => show tasks changed in the last 7 days"],
+            ..switch(
+                "modified",
+                Arguments::Optional(DATE_ARGUMENTS),
+                Values::Dates,
+                "filter or sort tasks by date of the last change, takes the same filtering methods as --date",
+            )
+        },
+        SwitchSpec {
+            examples: &[
+                "--sort modified:desc => recently changed tasks first",
+                "--sort deadline => same as --deadline without arguments",
+            ],
+            ..switch(
+                "sort",
+                Arguments::Required("<deadline | date | created | modified | thing>[:asc | :desc]"),
+                Values::SortKeys,
+                "sort tasks by given key, ascending by default, tasks without the date come first in both orders",
+            )
+        },
+        SwitchSpec {
+            aliases: &["alph"],
+            ..switch(
+                "alphabetical",
+                Arguments::Nothing,
+                Values::Nothing,
+                "sort tasks in alphabetical order by thing field",
+            )
+        },
+        SwitchSpec {
+            negatable: true,
+            examples: &[
+                "--date tomorrow => filters tasks equal provided d/m/y date part",
+                "--date tomorrow 12:30 => filters tasks equal to provided date",
+                "--date -gt 10/06/2023 12:30 => filters tasks by date later than given date",
+                "--date -ge 10/06/2023 12:30 => filters tasks by date later than or equal to given date",
+                "--date -lt 10/06/2023 12:30 => filters tasks by date earlier than given date",
+                "--date -le 10/06/2023 12:30 => filters tasks by date earlier than or equal to given date",
+                "--date -le today => date without hours:minutes stands for the whole day, so tasks due later today are included",
+                "--date -between today 20/06/2023 => filters tasks by date between given dates, both inclusive, date without hours:minutes includes whole day",
+                "--date this-week | last-week | next-week | last-7-days | next-7-days => filters tasks by date inside of the named range",
+                "--status completed --date this-week => tasks completed this week",
+                "--date -asc => sorts tasks by ascending date",
+                "--date -desc => sorts tasks by descending date",
+            ],
+            ..switch(
+                "date",
+                Arguments::Optional(DATE_ARGUMENTS),
+                Values::Dates,
+                "filter tasks by date, filters by the date of completed, postponed, expired or aborted status, if present, or deadline",
+            )
+        },
+        SwitchSpec {
+            negatable: true,
+            ..switch(
+                "overdue",
+                Arguments::Nothing,
+                Values::Nothing,
+                "show tasks that are not completed nor aborted and their date already passed",
+            )
+        },
+        switch(
+            "limit",
+            Arguments::Required("<Number>"),
+            Values::Nothing,
+            "display at most given number of tasks",
+        ),
+        switch(
+            "offset",
+            Arguments::Required("<Number>"),
+            Values::Nothing,
+            "skip given number of tasks before displaying",
+        ),
+        switch(
+            "head",
+            Arguments::Optional("[Number]"),
+            Values::Nothing,
+            "display first tasks, 10 if number is not supplied",
+        ),
+        switch(
+            "tail",
+            Arguments::Optional("[Number]"),
+            Values::Nothing,
+            "display last tasks, 10 if number is not supplied",
+        ),
+        SwitchSpec {
+            description: "print tasks in given format, table on the terminal and plain otherwise by default, honoured by --redirect",
+            examples: &[
+                "Fields of json, jsonl, csv and tsv formats: label, id, thing, status, status_date, deadline, created, modified, note, annotations, attachments, estimate, tracked",
+                "status is one of todo | completed | postponed | expired | aborted, dates are in RFC 3339 format",
+                "status_date is present for completed, postponed, expired and aborted status, otherwise it is null or empty",
+                "created and modified are null or empty for tasks saved before they were recorded",
+                "annotations are listed one per line as <date> <text>, attachments one per line, note, annotations and attachments are null or empty if the task has none",
+                "Table on the terminal is colored: red for expired, yellow for due today, dim for completed and aborted tasks, set NO_COLOR to turn colors off",
+            ],
+            ..FORMAT_SWITCH
+        },
+        switch(
+            "no-pager",
+            Arguments::Nothing,
+            Values::Nothing,
+            "do not pipe the output through pager, even if it does not fit the terminal",
+        ),
+        switch(
+            "redirect",
+            Arguments::Optional("[filename]"),
+            Values::Files,
+            "redirect stream to given file, if file does not exists it's created, takes relative path of current execution path or absolute path. If file is not supplied, creates file named stream.txt in working directory",
+        ),
+    ],
+    accepts_filters: false,
+    notes: &[
+        "--not-<switch> [arguments] => negate any filtering switch (thing, status, tag, note, deadline, date, created, modified, overdue), show tasks that the switch would filter out",
+        "    --not-status completed,aborted => show tasks that are still actionable",
+        "--limit, --offset, --head, --tail, --format and --no-pager are applied to the final result of filtering no matter on their position",
+        "Output that does not fit the terminal is piped through $PAGER or less",
+    ],
+};
+
+pub const SHOW_TASK: CommandSpec = CommandSpec {
+    name: "show task",
+    aliases: &[],
+    usage: "show task <Task lable ID> [switch]",
+    description: "Print task by id",
+    arguments: Values::TaskIds,
+    switches: &[
+        switch(
+            "history",
+            Arguments::Nothing,
+            Values::Nothing,
+            "display changelog of the task with dates, users and changed fields",
+        ),
+        SwitchSpec {
+            description: "print task in given format, see show tasks --help for the fields",
+            ..FORMAT_SWITCH
+        },
+    ],
+    accepts_filters: false,
+    notes: &[],
+};
+
+pub const ADD: CommandSpec = CommandSpec {
+    name: "add",
+    aliases: &["2", "add task"],
+    usage: "add [thing] [--deadline <date>] [--estimate <Duration>]",
+    description: "Add new task, without arguments asks for the thing and deadline",
+    arguments: Values::Nothing,
+    switches: &[
+        switch(
+            "deadline",
+            Arguments::Required("<Formatted date string -> format: 10/06/2023 12:30 | 10/06/2023 | tomorrow 12:30 | today 12:30 | next 12:30 | tomorrow | next | today | 12:30>"),
+            Values::Dates,
+            "deadline of the task, required if thing is supplied",
+        ),
+        ESTIMATE_SWITCH,
+    ],
+    accepts_filters: false,
+    notes: &[],
+};
+
+const ESTIMATE_SWITCH: SwitchSpec = SwitchSpec {
+    examples: &[
+        "--estimate 3h | 1h30m | 45m",
+        "--estimate none => remove the estimate",
+    ],
+    ..switch(
+        "estimate",
+        Arguments::Required("<Duration>"),
+        Values::Nothing,
+        "expected time to work on the task, compared with the tracked time by report estimates",
+    )
+};
+
+const YES_SWITCH: SwitchSpec = switch(
+    "yes",
+    Arguments::Nothing,
+    Values::Nothing,
+    "do not ask for confirmation",
+);
+
+const BULK_NOTE: &str = "Instead of the id, command takes filtering switches of show tasks (thing, status, tag, note, deadline, date, created, modified, overdue and their --not- variants), matched tasks are previewed and changed after confirmation";
+
+pub const DONE: CommandSpec = CommandSpec {
+    name: "done",
+    aliases: &[],
+    usage: "done <Task id | filtering switches> [--yes]",
+    description: "Mark task as completed",
+    arguments: Values::TaskIds,
+    switches: &[YES_SWITCH],
+    accepts_filters: true,
+    notes: &[
+        BULK_NOTE,
+        "    done --tag sprint-12 => mark every task tagged with #sprint-12 as completed",
+    ],
+};
+
+pub const EDIT: CommandSpec = CommandSpec {
+    name: "edit",
+    aliases: &["3", "edit task"],
+    usage: "edit <Task id> [--thing [String]] [--status [TaskStatus]] [--deadline [Date]] [--note <String>] [--estimate <Duration>] [--editor] | edit <filtering switches> [--set-thing <String>] [--set-status <TaskStatus>] [--set-deadline <Date>] [--yes]",
+    description: "Edit task, field without value is asked for in the interactive prompt",
+    arguments: Values::TaskIds,
+    switches: &[
+        switch(
+            "thing",
+            Arguments::Optional("[String]"),
+            Values::Nothing,
+            "new thing of the task",
+        ),
+        SwitchSpec {
+            examples: &[
+                "--status completed | todo | aborted",
+                "--status postponed tomorrow 12:30 => postpone task, date is relative to the previous date",
+            ],
+            ..switch(
+                "status",
+                Arguments::Optional("[TaskStatus]"),
+                Values::Statuses,
+                "new status of the task",
+            )
+        },
+        SwitchSpec {
+            examples: &[
+                "--deadline 20/10/2026 12:30 => move the deadline, unlike postponing the status is kept",
+                "--deadline next => move the deadline of expired task to the future to make it Todo again",
+            ],
+            ..switch(
+                "deadline",
+                Arguments::Optional("[Date]"),
+                Values::Dates,
+                "new deadline of the task",
+            )
+        },
+        SwitchSpec {
+            examples: &[
+                "--note \"call before noon\\nask for the invoice\" => note with two lines",
+                "--note \"\" => remove the note",
+            ],
+            ..switch(
+                "note",
+                Arguments::Required("<String>"),
+                Values::Nothing,
+                "replace multi-line note of the task, \\n starts a new line, cannot be combined with other switches",
+            )
+        },
+        SwitchSpec {
+            description: "replace the estimate of the task, cannot be combined with other switches",
+            ..ESTIMATE_SWITCH
+        },
+        switch(
+            "editor",
+            Arguments::Nothing,
+            Values::Nothing,
+            "edit the whole task as a document in $VISUAL or $EDITOR",
+        ),
+        switch(
+            "set-thing",
+            Arguments::Required("<String>"),
+            Values::Nothing,
+            "new thing of the tasks, --thing is a filter when tasks are selected by filtering switches",
+        ),
+        switch(
+            "set-status",
+            Arguments::Required("<TaskStatus>"),
+            Values::Statuses,
+            "new status of the tasks, --status is a filter when tasks are selected by filtering switches",
+        ),
+        switch(
+            "set-deadline",
+            Arguments::Required("<Date>"),
+            Values::Dates,
+            "new deadline of the tasks",
+        ),
+        YES_SWITCH,
+    ],
+    accepts_filters: true,
+    notes: &[
+        BULK_NOTE,
+        "    edit --date today --set-status postponed tomorrow => postpone all of today's tasks to tomorrow",
+    ],
+};
+
+pub const ANNOTATE: CommandSpec = CommandSpec {
+    name: "annotate",
+    aliases: &[],
+    usage: "annotate <Task id> <String>",
+    description: "Add timestamped annotation to the task, annotations are listed by show task",
+    arguments: Values::TaskIds,
+    switches: &[],
+    accepts_filters: false,
+    notes: &["    annotate 5 \"waiting on vendor reply\""],
+};
+
+pub const ATTACH: CommandSpec = CommandSpec {
+    name: "attach",
+    aliases: &[],
+    usage: "attach <Task id> <file | URL>",
+    description: "Link local file or URL to the task, files are kept by their absolute path",
+    arguments: Values::TaskIds,
+    switches: &[],
+    accepts_filters: false,
+    notes: &[
+        "    attach 5 ./design.pdf",
+        "    attach 5 https://example.com/spec",
+    ],
+};
+
+pub const OPEN: CommandSpec = CommandSpec {
+    name: "open",
+    aliases: &[],
+    usage: "open <Task id> [N]",
+    description: "Open attachment with given number in show task, every attachment of the task if it is not given",
+    arguments: Values::TaskIds,
+    switches: &[],
+    accepts_filters: false,
+    notes: &[
+        "Attachments are opened by $RUTODO_OPENER if set, otherwise by xdg-open, open on macOS or start on windows",
+    ],
+};
+
+pub const CHECK: CommandSpec = CommandSpec {
+    name: "check",
+    aliases: &[],
+    usage: "check",
+    description: "Report attached files that no longer exist, fails if there are any",
+    arguments: Values::Nothing,
+    switches: &[],
+    accepts_filters: false,
+    notes: &[],
+};
+
+pub const START: CommandSpec = CommandSpec {
+    name: "start",
+    aliases: &[],
+    usage: "start <Task id>",
+    description: "Start timer of the task, timer running on another task is stopped",
+    arguments: Values::TaskIds,
+    switches: &[],
+    accepts_filters: false,
+    notes: &["Tracked time of the task is shown by show task"],
+};
+
+pub const STOP: CommandSpec = CommandSpec {
+    name: "stop",
+    aliases: &[],
+    usage: "stop",
+    description: "Stop the running timer",
+    arguments: Values::Nothing,
+    switches: &[],
+    accepts_filters: false,
+    notes: &[],
+};
+
+pub const LOG_TIME: CommandSpec = CommandSpec {
+    name: "log-time",
+    aliases: &[],
+    usage: "log-time <Task id> <Duration>",
+    description: "Add time worked on the task without running the timer",
+    arguments: Values::TaskIds,
+    switches: &[],
+    accepts_filters: false,
+    notes: &["    log-time 5 1h30m", "    log-time 5 45m"],
+};
+
+pub const REPORT_ESTIMATES: CommandSpec = CommandSpec {
+    name: "report estimates",
+    aliases: &[],
+    usage: "report estimates [--date <Date>] [--tag <Tag>] [--status <TaskStatus[,TaskStatus]>]",
+    description: "Compare estimates with tracked time per task and per tag, ratio above 1 means the work took longer than estimated",
+    arguments: Values::Nothing,
+    switches: &[
+        SwitchSpec {
+            examples: &[
+                "--date last-week => tasks with time tracked last week",
+                "--date -between 01/10/2026 15/10/2026",
+            ],
+            ..switch(
+                "date",
+                Arguments::Required(DATE_ARGUMENTS),
+                Values::Dates,
+                "tasks with time tracked in the range, takes the same filtering methods as --date of show tasks, only time tracked in the range is counted",
+            )
+        },
+        switch(
+            "tag",
+            Arguments::Required("<Tag>"),
+            Values::Tags,
+            "tasks tagged with given tag",
+        ),
+        switch(
+            "status",
+            Arguments::Required("<TaskStatus[,TaskStatus]>"),
+            Values::Statuses,
+            "tasks with any of given statuses",
+        ),
+    ],
+    accepts_filters: false,
+    notes: &["    report estimates --status completed --date last-week => how accurate were estimates of tasks completed last week"],
+};
+
+pub const REOPEN: CommandSpec = CommandSpec {
+    name: "reopen",
+    aliases: &[],
+    usage: "reopen <Task id> [--deadline <Date>] --reason <String>",
+    description: "Make expired or aborted task Todo again, the reason is recorded in the history",
+    arguments: Values::TaskIds,
+    switches: &[
+        switch(
+            "deadline",
+            Arguments::Required("<Date>"),
+            Values::Dates,
+            "new deadline of the task, required if the deadline already passed",
+        ),
+        switch(
+            "reason",
+            Arguments::Required("<String>"),
+            Values::Nothing,
+            "why the task is reopened",
+        ),
+    ],
+    accepts_filters: false,
+    notes: &[
+        "    reopen 4 --deadline next 12:30 --reason waiting for the client is over => revive expired task",
+    ],
+};
+
+pub const REVERT: CommandSpec = CommandSpec {
+    name: "revert",
+    aliases: &[],
+    usage: "revert <Task id> <version> [--thing] [--status] [--deadline]",
+    description: "Restore task as it was before the change with given number in show task <Task id> --history, all fields unless some are selected",
+    arguments: Values::TaskIds,
+    switches: &[
+        switch("thing", Arguments::Nothing, Values::Nothing, "restore the thing"),
+        switch("status", Arguments::Nothing, Values::Nothing, "restore the status"),
+        switch("deadline", Arguments::Nothing, Values::Nothing, "restore the deadline"),
+    ],
+    accepts_filters: false,
+    notes: &[
+        "    revert 4 2 --status => status of task 4 as it was before its second change",
+        "    versions of a deleted task are not applied to another task that got its id",
+    ],
+};
+
+pub const DELETE: CommandSpec = CommandSpec {
+    name: "delete",
+    aliases: &["4", "delete task", "rm"],
+    usage: "delete | rm <Task id | all | filtering switches> [--yes]",
+    description:
+        "Move task by id or all tasks to the trash, deleting all tasks asks for confirmation",
+    arguments: Values::TaskIdsOrAll,
+    switches: &[YES_SWITCH],
+    accepts_filters: true,
+    notes: &[
+        BULK_NOTE,
+        "    delete --status aborted => delete every aborted task",
+    ],
+};
+
+pub const LOG: CommandSpec = CommandSpec {
+    name: "log",
+    aliases: &[],
+    usage: "log [--since <Date>] [--user <String>] [--op <Operation>]",
+    description: "Display chronological feed of changes of all tasks, including the deleted ones and automatic expirations",
+    arguments: Values::Nothing,
+    switches: &[
+        SwitchSpec {
+            examples: &["--since last-7-days | this-week => changes since the start of the named range"],
+            ..switch(
+                "since",
+                Arguments::Required("<Date>"),
+                Values::Dates,
+                "changes made since the date",
+            )
+        },
+        switch(
+            "user",
+            Arguments::Required("<String>"),
+            Values::Nothing,
+            "changes made by the user",
+        ),
+        switch(
+            "op",
+            Arguments::Required("<add | edit | expire | annotate | attach | track | reopen | revert | delete | restore>"),
+            Values::Operations,
+            "changes made by the operation",
+        ),
+    ],
+    accepts_filters: false,
+    notes: &["    log --since today --op expire => tasks that expired today"],
+};
+
+pub const UNDO: CommandSpec = CommandSpec {
+    name: "undo",
+    aliases: &[],
+    usage: "undo [N]",
+    description: "Revert last N operations that changed tasks (add, edit, done, delete, --parse), 1 by default",
+    arguments: Values::Nothing,
+    switches: &[],
+    accepts_filters: false,
+    notes: &[
+        "Deleted tasks are restored together with their history",
+        "Operations are kept in tasks_journal.txt next to tasks.txt, so undo works across sessions",
+    ],
+};
+
+pub const REDO: CommandSpec = CommandSpec {
+    name: "redo",
+    aliases: &[],
+    usage: "redo [N]",
+    description: "Apply again last N undone operations, 1 by default",
+    arguments: Values::Nothing,
+    switches: &[],
+    accepts_filters: false,
+    notes: &["Undone operations cannot be redone after another change of tasks"],
+};
+
+const TRASH_NOTES: &[&str] = &[
+    "Deleted tasks are kept in the trash with their history until it is emptied, set RUTODO_TRASH_DAYS to remove them after given number of days",
+];
+
+pub const TRASH_LIST: CommandSpec = CommandSpec {
+    name: "trash list",
+    aliases: &["trash", "show deleted"],
+    usage: "trash list | show deleted",
+    description: "Display deleted tasks with the date and the user of deletion",
+    arguments: Values::Nothing,
+    switches: &[],
+    accepts_filters: false,
+    notes: TRASH_NOTES,
+};
+
+pub const TRASH_RESTORE: CommandSpec = CommandSpec {
+    name: "trash restore",
+    aliases: &["restore"],
+    usage: "trash restore | restore <Task id>",
+    description:
+        "Restore deleted task with its history, task gets the first available id if its id is taken",
+    arguments: Values::Nothing,
+    switches: &[],
+    accepts_filters: false,
+    notes: TRASH_NOTES,
+};
+
+pub const TRASH_EMPTY: CommandSpec = CommandSpec {
+    name: "trash empty",
+    aliases: &[],
+    usage: "trash empty [--yes]",
+    description: "Permanently remove every deleted task",
+    arguments: Values::Nothing,
+    switches: &[YES_SWITCH],
+    accepts_filters: false,
+    notes: TRASH_NOTES,
+};
+
+pub const TUI: CommandSpec = CommandSpec {
+    name: "tui",
+    aliases: &[],
+    usage: "tui",
+    description: "Full-screen interface with task list, detail and history of the selected task",
+    arguments: Values::Nothing,
+    switches: &[],
+    accepts_filters: false,
+    notes: &[
+        "j/k or arrows => move, PageUp/PageDown, g/G => first/last task",
+        "/ => live filter, takes switches of show tasks, plain text matches the start of thing",
+        "a => add, e => edit thing, c => complete, p => postpone, d => delete (to the trash)",
+        "q or Esc => quit, changes can be reverted by undo",
+    ],
+};
+
+pub const COMPLETIONS: CommandSpec = CommandSpec {
+    name: "completions",
+    aliases: &[],
+    usage: "completions <bash | zsh | fish>",
+    description: "Print completion script for the shell",
+    arguments: Values::Shells,
+    switches: &[],
+    accepts_filters: false,
+    notes: &[
+        "bash => rutodo completions bash > /etc/bash_completion.d/rutodo",
+        "zsh  => rutodo completions zsh > \"${fpath[1]}/_rutodo\"",
+        "fish => rutodo completions fish > ~/.config/fish/completions/rutodo.fish",
+    ],
+};
+
+pub const HELP: CommandSpec = CommandSpec {
+    name: "help",
+    aliases: &[],
+    usage: "help",
+    description: "Display available commands",
+    arguments: Values::Nothing,
+    switches: &[],
+    accepts_filters: false,
+    notes: &[],
+};
+
+pub const COMMANDS: [&CommandSpec; 26] = [
+    &SHOW_TASKS,
+    &SHOW_TASK,
+    &ADD,
+    &DONE,
+    &EDIT,
+    &ANNOTATE,
+    &ATTACH,
+    &OPEN,
+    &CHECK,
+    &START,
+    &STOP,
+    &LOG_TIME,
+    &REPORT_ESTIMATES,
+    &REOPEN,
+    &REVERT,
+    &DELETE,
+    &LOG,
+    &UNDO,
+    &REDO,
+    &TRASH_LIST,
+    &TRASH_RESTORE,
+    &TRASH_EMPTY,
+    &TUI,
+    &COMPLETIONS,
+    &HELP,
+    &CommandSpec {
+        name: "__complete",
+        aliases: &[],
+        usage: "__complete [words]",
+        description: "Print candidates for the last word, used by completion scripts",
+        arguments: Values::Nothing,
+        switches: &[],
+        accepts_filters: false,
+        notes: &[],
+    },
+];
+
+/// Finds command by the leading words of the input, e.g. ["show", "task", "4"] => show task
+pub fn find_command<S: AsRef<str>>(words: &[S]) -> Option<(&'static CommandSpec, usize)> {
+    let words = words
+        .iter()
+        .map(|word| word.as_ref().to_lowercase())
+        .collect::<Vec<_>>();
+
+    COMMANDS
+        .iter()
+        .flat_map(|command| {
+            std::iter::once(command.name)
+                .chain(command.aliases.iter().copied())
+                .map(move |name| (*command, name))
+        })
+        .filter(|(_, name)| {
+            let name_words = name.split_whitespace().collect::<Vec<_>>();
+            name_words.len() <= words.len() && name_words.iter().zip(&words).all(|(a, b)| a == b)
+        })
+        // the longest name wins, so "show task" does not match "show tasks"
+        .max_by_key(|(_, name)| name.split_whitespace().count())
+        .map(|(command, name)| (command, name.split_whitespace().count()))
+}
+
+impl SwitchSpec {
+    fn matches(&self, name: &str) -> bool {
+        self.name == name || self.aliases.contains(&name)
+    }
+
+    fn help(&self) -> String {
+        let names = std::iter::once(self.name)
+            .chain(self.aliases.iter().copied())
+            .map(|name| format!("--{name}"))
+            .collect::<Vec<_>>()
+            .join(" | ");
+
+        let arguments = match self.arguments {
+            Arguments::Nothing => String::new(),
+            Arguments::Required(placeholder) | Arguments::Optional(placeholder) => {
+                format!(" {placeholder}")
+            }
+        };
+
+        let mut help = format!("    {names}{arguments} => {}\n", self.description);
+        for example in self.examples {
+            help.push_str(&format!("        {example}\n"));
+        }
+
+        help
+    }
+}
+
+impl CommandSpec {
+    pub fn find_switch(&self, switch: &str) -> Option<&SwitchSpec> {
+        let name = switch.trim_start_matches("--").to_lowercase();
+
+        if let Some(switch) = self.switches.iter().find(|x| x.matches(&name)) {
+            return Some(switch);
+        }
+
+        if self.accepts_filters {
+            if let Some(switch) = SHOW_TASKS.find_filtering_switch(&name) {
+                return Some(switch);
+            }
+        }
+
+        let name = name.strip_prefix("not-")?;
+        self.switches
+            .iter()
+            .find(|x| x.negatable && x.matches(name))
+    }
+
+    /// Filtering switches are the ones that can be negated
+    fn find_filtering_switch(&self, name: &str) -> Option<&SwitchSpec> {
+        let name = name.strip_prefix("not-").unwrap_or(name);
+
+        self.switches
+            .iter()
+            .find(|x| x.negatable && x.matches(name))
+    }
+
+    fn all_switches(&self) -> impl Iterator<Item = &SwitchSpec> {
+        let filters = SHOW_TASKS
+            .switches
+            .iter()
+            .filter(|x| self.accepts_filters && x.negatable);
+
+        self.switches.iter().chain(filters)
+    }
+
+    pub fn help(&self) -> String {
+        let mut help = format!(
+            "Description: {}\nUsage: {}\nOutput: UTF-8 encoded string\n",
+            self.description, self.usage
+        );
+
+        for switch in self.switches {
+            help.push('\n');
+            help.push_str(&switch.help());
+        }
+
+        help.push_str("\n    --help => Display this message\n");
+
+        if !self.notes.is_empty() {
+            help.push('\n');
+            for note in self.notes {
+                help.push_str(&format!("{note}\n"));
+            }
+        }
+
+        help
+    }
+
+    pub fn is_help_requested(switches: &Option<Switches>) -> bool {
+        match switches {
+            Some(switches) => switches
+                .iter()
+                .any(|(switch, _)| switch.to_lowercase() == "--help"),
+            None => false,
+        }
+    }
+
+    /// Rejects unknown switches, missing arguments and arguments of switches that do not take any
+    pub fn validate(&self, switches: &Option<Switches>) -> Result<(), String> {
+        let switches = match switches {
+            Some(switches) => switches,
+            None => return Ok(()),
+        };
+
+        for (name, args) in switches {
+            if name.to_lowercase() == "--help" {
+                continue;
+            }
+
+            let switch = self.find_switch(name).ok_or(format!(
+                "Unknown switch {name} for {}, see {} --help",
+                self.name, self.name
+            ))?;
+
+            match (switch.arguments, args) {
+                (Arguments::Required(placeholder), None) => {
+                    return Err(format!(
+                        "switch {name} requiers additional arguments: {placeholder}"
+                    ))
+                }
+                (Arguments::Nothing, Some(args)) => {
+                    return Err(format!(
+                        "switch {name} does not take any arguments, got: {}",
+                        args.join(" ")
+                    ))
+                }
+                _ => (),
+            }
+        }
+
+        Ok(())
+    }
+}
+
+/// Every command with its usage, used by rutodo help
+pub fn usage() -> String {
+    let mut usage = "Usage: rutodo [command] [arguments] [switches]\nWithout command spawns the interactive prompt\n".to_string();
+
+    for command in COMMANDS.iter().filter(|x| !x.name.starts_with("__")) {
+        usage.push_str(&format!("{} => {}\n", command.usage, command.description));
+    }
+
+    usage.push_str(
+        "Every command takes --help switch\nExit code is 0 on success and 1 if command failed\n",
+    );
+
+    usage
+}
+
+/// Commands available in the interactive prompt, used by its help action
+pub fn interactive_usage() -> String {
+    let mut usage = "Available actions:\n".to_string();
+
+    for command in COMMANDS.iter().filter(|x| !x.name.starts_with("__")) {
+        usage.push_str(&format!("{} => {}\n", command.usage, command.description));
+    }
+
+    usage.push_str("<action> --help => Display help message of the action\n");
+    usage.push_str("cls => Clear the console\n");
+    usage.push_str("exit => Terminates current procces\n");
+
+    usage
+}
+
+fn values(kind: Values, tasks: &[Task]) -> Vec<String> {
+    let ids = || tasks.iter().map(|task| task.id().to_string());
+
+    match kind {
+        Values::Nothing | Values::Files => vec![],
+        Values::TaskIds => ids().collect(),
+        Values::TaskIdsOrAll => ids().chain(std::iter::once("all".to_string())).collect(),
+        Values::Statuses => STATUS_NAMES.iter().map(|x| x.to_string()).collect(),
+        Values::Formats => ["plain", "json", "jsonl", "csv", "tsv", "table"]
+            .map(String::from)
+            .to_vec(),
+        Values::Dates => [
+            "today",
+            "tomorrow",
+            "next",
+            "-gt",
+            "-ge",
+            "-lt",
+            "-le",
+            "-between",
+            "-asc",
+            "-desc",
+            "this-week",
+            "last-week",
+            "next-week",
+            "last-7-days",
+            "next-7-days",
+        ]
+        .map(String::from)
+        .to_vec(),
+        Values::Tags => {
+            let mut tags = tasks.iter().flat_map(Task::tags).collect::<Vec<_>>();
+            tags.sort();
+            tags.dedup();
+            tags
+        }
+        Values::Shells => ["bash", "zsh", "fish"].map(String::from).to_vec(),
+        Values::SortKeys => ["deadline", "date", "created", "modified", "thing"]
+            .into_iter()
+            .flat_map(|key| [key.to_string(), format!("{key}:asc"), format!("{key}:desc")])
+            .collect(),
+        Values::Operations => ChangeKind::ALL
+            .iter()
+            .map(|kind| kind.name().to_string())
+            .collect(),
+    }
+}
+
+/// Candidates for the last of the words, which is the one being completed (it can be empty)
+pub fn complete(words: &[String], tasks: &[Task]) -> Vec<String> {
+    let (current, previous) = match words.split_last() {
+        Some((current, previous)) => (current.as_str(), previous),
+        None => ("", &[][..]),
+    };
+
+    let candidates = match find_command(previous) {
+        None => {
+            // complete name of the command, multi word names are completed word by word
+            let typed = previous.join(" ").to_lowercase();
+
+            COMMANDS
+                .iter()
+                .filter(|command| !command.name.starts_with("__"))
+                .flat_map(|command| {
+                    std::iter::once(command.name).chain(command.aliases.iter().copied())
+                })
+                .filter(|name| !name.chars().all(|c| c.is_numeric()))
+                .filter_map(|name| {
+                    let rest = if typed.is_empty() {
+                        name
+                    } else {
+                        name.strip_prefix(&format!("{typed} "))?
+                    };
+                    rest.split_whitespace().next().map(String::from)
+                })
+                .fold(vec![], |mut names, name| {
+                    if !names.contains(&name) {
+                        names.push(name);
+                    }
+                    names
+                })
+        }
+        Some((command, consumed)) => {
+            let previous = &previous[consumed..];
+
+            if current.starts_with("--") {
+                command
+                    .all_switches()
+                    .flat_map(|switch| {
+                        let negated = switch.negatable.then(|| format!("--not-{}", switch.name));
+
+                        std::iter::once(format!("--{}", switch.name))
+                            .chain(switch.aliases.iter().map(|alias| format!("--{alias}")))
+                            .chain(negated)
+                    })
+                    .chain(std::iter::once("--help".to_string()))
+                    .fold(vec![], |mut switches, switch| {
+                        if !switches.contains(&switch) {
+                            switches.push(switch);
+                        }
+                        switches
+                    })
+            } else {
+                match previous.iter().rposition(|word| word.starts_with("--")) {
+                    Some(idx) => match command.find_switch(&previous[idx]) {
+                        Some(switch) if switch.arguments != Arguments::Nothing => {
+                            values(switch.values, tasks)
+                        }
+                        _ => vec![],
+                    },
+                    None if previous.is_empty() => values(command.arguments, tasks),
+                    None => vec![],
+                }
+            }
+        }
+    };
+
+    candidates
+        .into_iter()
+        .filter(|candidate| candidate.starts_with(current))
+        .collect()
+}
+
+/// Scripts ask the binary for candidates, so task ids and tags are always up to date
+pub fn completion_script(shell: &str) -> Result<String, String> {
+    let script = match shell {
+        "bash" => {
+            r#"# bash completion for rutodo
+_rutodo() {
+    local IFS=$'\n'
+    COMPREPLY=($(rutodo __complete "${COMP_WORDS[@]:1:COMP_CWORD}" 2>/dev/null))
+}
+complete -o default -F _rutodo rutodo
+"#
+        }
+        "zsh" => {
+            r#"#compdef rutodo
+# zsh completion for rutodo
+_rutodo() {
+    local -a candidates
+    candidates=("${(@f)$(rutodo __complete "${(@)words[2,CURRENT]}" 2>/dev/null)}")
+    candidates=(${candidates:#})
+    if (( ${#candidates} )); then
+        compadd -a candidates
+    else
+        _files
+    fi
+}
+compdef _rutodo rutodo
+"#
+        }
+        "fish" => {
+            r#"# fish completion for rutodo
+function __rutodo_complete
+    set -l words (commandline -opc) (commandline -ct)
+    rutodo __complete $words[2..-1] 2>/dev/null
+end
+complete -c rutodo -f -a '(__rutodo_complete)'
+"#
+        }
+        other => {
+            return Err(format!(
+                "Unsupported shell {other}, available shells: bash | zsh | fish"
+            ))
+        }
+    };
+
+    Ok(script.to_string())
+}
@@ -0,0 +1,374 @@
+use crate::history::{self, ChangeKind};
+use crate::{tasks_file_manager, DateTimeFormatter, Deadline, HistoryEntry, Task, TaskStatus};
+use chrono::DateTime;
+use std::collections::HashMap;
+use std::fs::{self, OpenOptions};
+use std::io;
+use std::path::{Path, PathBuf};
+use std::process::Command;
+
+/// Fields of the task after editing, None stands for unchanged field
+#[derive(Debug, Default)]
+struct EditedTask {
+    thing: Option<String>,
+    status: Option<TaskStatus>,
+    deadline: Option<Deadline>,
+    note: Option<String>,
+}
+
+/// Opens the task as a text document in $VISUAL or $EDITOR, the editor is reopened until the document is valid.
+/// Saving an empty document cancels the edit
+pub fn edit_task(
+    tasks: &mut [Task],
+    tasks_history: &mut HashMap<String, Vec<HistoryEntry>>,
+    task_label_number: &str,
+) -> Result<(), String> {
+    let task = Task::find_by_label_number(tasks, task_label_number)?;
+
+    task.check_editable()?;
+
+    let path = create_document_file(task_label_number)?;
+    let mut document = document(task);
+
+    let edited = loop {
+        fs::write(&path, &document).map_err(|err| err.to_string())?;
+        open_editor(&path)?;
+
+        let content = fs::read_to_string(&path).map_err(|err| err.to_string())?;
+
+        if content
+            .lines()
+            .all(|line| line.trim().is_empty() || line.starts_with('#'))
+        {
+            let _ = fs::remove_file(&path);
+            println!("Document is empty, nothing was changed");
+            return Ok(());
+        }
+
+        match parse_document(task, &content) {
+            Ok(edited) => break edited,
+            Err(errors) => {
+                for error in &errors {
+                    eprintln!("{error}");
+                }
+
+                // user's version is kept, errors are put on top of it
+                let content = content
+                    .lines()
+                    .filter(|line| !line.starts_with("# Error: "))
+                    .collect::<Vec<_>>()
+                    .join("\n");
+
+                document = errors
+                    .iter()
+                    .map(|error| format!("# Error: {error}\n"))
+                    .collect::<String>()
+                    + &content
+                    + "\n";
+            }
+        }
+    };
+
+    let _ = fs::remove_file(&path);
+
+    if edited.thing.is_none()
+        && edited.status.is_none()
+        && edited.deadline.is_none()
+        && edited.note.is_none()
+    {
+        println!("Nothing was changed");
+        return Ok(());
+    }
+
+    let prev_task = task.clone();
+
+    if let Some(thing) = edited.thing {
+        task.thing = format!("\"{thing}\"");
+    }
+
+    if let Some(status) = edited.status {
+        task.status = status;
+    }
+
+    if let Some(deadline) = edited.deadline {
+        task.deadline = deadline;
+    }
+
+    if let Some(note) = edited.note {
+        task.note = note;
+    }
+
+    history::record(tasks_history, ChangeKind::Edit, prev_task, task);
+
+    println!("Updated task:\n{task}");
+
+    tasks_file_manager::save(tasks, tasks_history);
+
+    Ok(())
+}
+
+/// Temp directory is shared with other users, so the file gets a unique name and an existing file is never reused
+fn create_document_file(task_label_number: &str) -> Result<PathBuf, String> {
+    for _ in 0..10 {
+        let path = std::env::temp_dir().join(format!(
+            "rutodo-task-{task_label_number}-{}.txt",
+            Task::new_uid()
+        ));
+
+        let mut options = OpenOptions::new();
+        options.write(true).create_new(true);
+        #[cfg(unix)]
+        std::os::unix::fs::OpenOptionsExt::mode(&mut options, 0o600);
+
+        match options.open(&path) {
+            Ok(_) => return Ok(path),
+            Err(err) if err.kind() == io::ErrorKind::AlreadyExists => continue,
+            Err(err) => return Err(format!("Could not create {}: {err}", path.display())),
+        }
+    }
+
+    Err("Could not create a unique file for the document".to_string())
+}
+
+fn open_editor(path: &Path) -> Result<(), String> {
+    let editor = std::env::var("VISUAL")
+        .or_else(|_| std::env::var("EDITOR"))
+        .ok()
+        .filter(|editor| !editor.trim().is_empty())
+        .unwrap_or(if cfg!(windows) { "notepad" } else { "vi" }.to_string());
+
+    // editor can be given with arguments, e.g. "code --wait"
+    let mut words = editor.split_whitespace();
+    let program = words.next().unwrap_or_default();
+
+    let status = Command::new(program)
+        .args(words)
+        .arg(path)
+        .status()
+        .map_err(|err| format!("Could not start the editor {program}: {err}"))?;
+
+    if !status.success() {
+        return Err(format!(
+            "Editor {program} exited with {status}, nothing was changed"
+        ));
+    }
+
+    Ok(())
+}
+
+fn document(task: &Task) -> String {
+    format!(
+        "# Editing {}, lines starting with # are ignored, save empty document to cancel\n\
+         # Status: todo | completed | aborted | postponed <date relative to the previous date> | Postponed(10/06/2023 12:30)\n\
+         # Deadline: 10/06/2023 12:30 | 10/06/2023 | tomorrow 12:30 | today 12:30 | next 12:30 | tomorrow | next | today | 12:30\n\
+         # Tags: comma separated, added tags are appended to the thing as #tag, removed ones are taken out of it\n\
+         # Note: every line below it up to the end of the document\n\
+         Thing: {}\n\
+         Status: {}\n\
+         Deadline: {}\n\
+         Tags: {}\n\
+         Note:\n{}",
+        task.label,
+        task.unquoted_thing(),
+        task.status,
+        DateTime::date_user_formating(task.deadline.date),
+        task.tags().join(", "),
+        task.note
+            .lines()
+            .map(|line| format!("{line}\n"))
+            .collect::<String>(),
+    )
+}
+
+/// Validates every field, all errors are reported at once
+fn parse_document(task: &Task, content: &str) -> Result<EditedTask, Vec<String>> {
+    let mut fields: HashMap<String, String> = HashMap::new();
+    let mut errors = vec![];
+    let mut note: Option<Vec<&str>> = None;
+
+    for line in content.lines() {
+        if line.starts_with('#') {
+            continue;
+        }
+
+        // lines of the note can look like fields
+        if let Some(note) = note.as_mut() {
+            note.push(line);
+            continue;
+        }
+
+        if line.trim().is_empty() {
+            continue;
+        }
+
+        match line.split_once(':') {
+            Some((key, value)) if key.trim().eq_ignore_ascii_case("note") => {
+                note = Some(vec![value.trim()]);
+            }
+            Some((key, value)) => {
+                let key = key.trim().to_lowercase();
+
+                if !["thing", "status", "deadline", "tags"].contains(&key.as_str()) {
+                    errors.push(format!("Unknown field {key}"));
+                } else if fields
+                    .insert(key.clone(), value.trim().to_string())
+                    .is_some()
+                {
+                    errors.push(format!("Field {key} is given more than once"));
+                }
+            }
+            None => errors.push(format!("Expected \"Field: value\", got: {line}")),
+        }
+    }
+
+    let mut edited = EditedTask::default();
+
+    let mut thing = match fields.get("thing") {
+        Some(thing) if !thing.is_empty() => thing.clone(),
+        _ => {
+            errors.push("Task thing cannot be empty".to_string());
+            String::new()
+        }
+    };
+
+    if let Some(tags) = fields.get("tags") {
+        thing = apply_tags(&thing, tags);
+    }
+
+    if thing != task.unquoted_thing() && !thing.is_empty() {
+        edited.thing = Some(thing);
+    }
+
+    match fields.get("status") {
+        Some(status) if status.eq_ignore_ascii_case(&task.status.to_string()) => (),
+        Some(status) => match parse_status(task, status) {
+            Ok(status) => edited.status = Some(status),
+            Err(err) => errors.push(err),
+        },
+        None => (),
+    }
+
+    if let Some(note) = note {
+        let note = note.join("\n").trim().to_string();
+
+        if note != task.note {
+            edited.note = Some(note);
+        }
+    }
+
+    match fields.get("deadline") {
+        Some(deadline) if *deadline == DateTime::date_user_formating(task.deadline.date) => (),
+        Some(deadline) => match Deadline::new(deadline) {
+            Ok(deadline) => edited.deadline = Some(deadline),
+            Err(_) => errors.push(format!("Could not parse the deadline: {deadline}")),
+        },
+        None => (),
+    }
+
+    if errors.is_empty() {
+        Ok(edited)
+    } else {
+        Err(errors)
+    }
+}
+
+/// Status as typed by the user or as displayed, e.g. Postponed(10/06/2023 12:30) or Completed(10/06/2023 12:30)
+fn parse_status(task: &Task, status: &str) -> Result<TaskStatus, String> {
+    let lowercase = status.to_lowercase();
+    let date_of = |name: &str| {
+        lowercase
+            .strip_prefix(&format!("{name}("))
+            .and_then(|date| date.strip_suffix(')'))
+            .map(|date| {
+                DateTime::parse_formated_string_to_datetime(&date.to_string(), DateTime::date_now())
+                    .map_err(|err| format!("Could not parse the {name} date: {err}"))
+            })
+    };
+
+    if let Some(date) = date_of("postponed") {
+        return date.map(TaskStatus::Postponed);
+    }
+
+    if let Some(date) = date_of("completed") {
+        return date.map(TaskStatus::Completed);
+    }
+
+    task.parse_status(status)
+}
+
+/// Adds #tag for tags missing in the thing and removes #tag words of tags that are not listed,
+/// the rest of the thing is kept as it was typed, including its spacing
+fn apply_tags(thing: &str, tags: &str) -> String {
+    let tags = tags
+        .split(',')
+        .map(|tag| tag.trim().trim_start_matches('#').to_lowercase())
+        .filter(|tag| !tag.is_empty())
+        .collect::<Vec<_>>();
+
+    let tag_of = |word: &str| {
+        word.strip_prefix('#').map(|tag| {
+            tag.trim_end_matches([',', '.', ';', ':', '!', '?'])
+                .to_lowercase()
+        })
+    };
+
+    let mut edited = String::new();
+    let mut present = vec![];
+    let mut rest = thing;
+
+    // every word goes with the whitespace before it, so a removed tag takes only its own space
+    while !rest.is_empty() {
+        let word_start = rest
+            .find(|c: char| !c.is_whitespace())
+            .unwrap_or(rest.len());
+        let word_end = rest[word_start..]
+            .find(char::is_whitespace)
+            .map_or(rest.len(), |end| word_start + end);
+        let (space, word) = (&rest[..word_start], &rest[word_start..word_end]);
+        rest = &rest[word_end..];
+
+        match tag_of(word) {
+            Some(tag) if !tags.contains(&tag) => continue,
+            Some(tag) => present.push(tag),
+            None => (),
+        }
+
+        edited.push_str(space);
+        edited.push_str(word);
+    }
+
+    // space of the following word is left at the start if the first word was removed
+    if !thing.starts_with(char::is_whitespace) {
+        edited = edited.trim_start().to_string();
+    }
+
+    for tag in tags {
+        if !present.contains(&tag) {
+            if !edited.is_empty() {
+                edited.push(' ');
+            }
+            edited.push_str(&format!("#{tag}"));
+        }
+    }
+
+    edited
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn apply_tags_keeps_spacing_of_the_thing() {
+        assert_eq!(
+            apply_tags("call  bob #work  today", "work"),
+            "call  bob #work  today"
+        );
+        assert_eq!(apply_tags("call  bob #work  today", ""), "call  bob  today");
+        assert_eq!(apply_tags("#work call  bob", ""), "call  bob");
+        assert_eq!(
+            apply_tags("call  bob #Work", "work, home"),
+            "call  bob #Work #home"
+        );
+    }
+}
@@ -0,0 +1,244 @@
+use crate::cli_manager::{self, Switches};
+use crate::history::{self, ChangeKind};
+use crate::{tasks_file_manager, timer, HistoryEntry, OutputFormat, Task};
+use chrono::Duration;
+use std::collections::{BTreeMap, HashMap};
+
+/// Sets the estimate of the task, e.g. 3h or 1h30m, none removes it
+pub fn set_estimate(
+    tasks: &mut [Task],
+    tasks_history: &mut HashMap<String, Vec<HistoryEntry>>,
+    task_label_number: &str,
+    estimate: &str,
+) -> Result<(), String> {
+    let estimate = parse_estimate(estimate)?;
+
+    let task = Task::find_by_label_number(tasks, task_label_number)?;
+    let prev_task = task.clone();
+
+    task.estimate = estimate;
+
+    if *task == prev_task {
+        println!("Nothing was changed");
+        return Ok(());
+    }
+
+    history::record(tasks_history, ChangeKind::Edit, prev_task, task);
+
+    println!("Updated task:\n{task}{}", describe(task));
+
+    tasks_file_manager::save(tasks, tasks_history);
+
+    Ok(())
+}
+
+/// None for "none", otherwise the duration given the same way as to log-time
+pub fn parse_estimate(estimate: &str) -> Result<Option<Duration>, String> {
+    match estimate.trim().to_lowercase().as_str() {
+        "none" => Ok(None),
+        estimate => timer::parse_duration(estimate).map(Some),
+    }
+}
+
+/// Tracked time divided by the estimate, above 1 the task took longer than estimated
+fn ratio(tracked: Duration, estimate: Duration) -> f64 {
+    tracked.num_seconds() as f64 / estimate.num_seconds() as f64
+}
+
+struct Totals {
+    tasks: usize,
+    estimate: Duration,
+    tracked: Duration,
+}
+
+impl Totals {
+    fn new() -> Self {
+        Totals {
+            tasks: 0,
+            estimate: Duration::zero(),
+            tracked: Duration::zero(),
+        }
+    }
+
+    fn add(&mut self, estimate: Duration, tracked: Duration) {
+        self.tasks += 1;
+        self.estimate = self.estimate + estimate;
+        self.tracked = self.tracked + tracked;
+    }
+
+    fn describe(&self) -> String {
+        format!(
+            "{} tasks, estimated {}, tracked {}, ratio {:.2}",
+            self.tasks,
+            timer::format_duration(self.estimate),
+            timer::format_duration(self.tracked),
+            ratio(self.tracked, self.estimate)
+        )
+    }
+}
+
+/// Estimated tasks selected by filtering switches of show tasks with their estimate and tracked time,
+/// --date selects tasks by the time tracked in the range instead of their dates
+fn estimated(
+    tasks: &[Task],
+    switches: Option<Switches>,
+) -> Result<Vec<(Task, Duration, Duration)>, String> {
+    let (from, to) = cli_manager::date_window(&switches)?;
+    let in_window = from.is_some() || to.is_some();
+
+    let switches = switches
+        .map(|switches| {
+            switches
+                .into_iter()
+                .filter(|(switch, _)| switch.to_lowercase() != "--date")
+                .collect::<Switches>()
+        })
+        .filter(|switches| !switches.is_empty());
+
+    let tasks = cli_manager::filter_tasks(tasks, switches, OutputFormat::Plain)?;
+
+    Ok(tasks
+        .into_iter()
+        .filter_map(|task| {
+            let estimate = task.estimate?;
+            let tracked = timer::total_between(&task, from, to);
+            // estimated tasks without time tracked in the range would only lower the ratio
+            (!in_window || tracked > Duration::zero()).then_some((task, estimate, tracked))
+        })
+        .collect())
+}
+
+/// Compares estimates with tracked time per task and per tag, tasks are selected by filtering switches of show tasks,
+/// with --date only the time tracked inside the range is counted
+pub fn report(tasks: &[Task], switches: Option<Switches>) -> Result<(), String> {
+    let estimated = estimated(tasks, switches)?;
+
+    if estimated.is_empty() {
+        println!("No estimated tasks found");
+        return Ok(());
+    }
+
+    let mut total = Totals::new();
+    let mut by_tag: BTreeMap<String, Totals> = BTreeMap::new();
+
+    println!("Tasks:");
+    for (task, estimate, tracked) in &estimated {
+        println!(
+            "    {} {}: estimated {}, tracked {}, ratio {:.2}",
+            task.label,
+            task.thing,
+            timer::format_duration(*estimate),
+            timer::format_duration(*tracked),
+            ratio(*tracked, *estimate)
+        );
+
+        total.add(*estimate, *tracked);
+
+        for tag in task.tags() {
+            by_tag
+                .entry(tag)
+                .or_insert_with(Totals::new)
+                .add(*estimate, *tracked);
+        }
+    }
+
+    if !by_tag.is_empty() {
+        println!("Tags:");
+        for (tag, totals) in &by_tag {
+            println!("    #{tag}: {}", totals.describe());
+        }
+    }
+
+    println!("Total: {}", total.describe());
+
+    Ok(())
+}
+
+/// Estimate printed under the task, empty if the task has none
+pub fn describe(task: &Task) -> String {
+    match task.estimate {
+        Some(estimate) => format!("Estimate: {}\n", timer::format_duration(estimate)),
+        None => String::new(),
+    }
+}
+
+// Estimate of the task line is written as it is shown: estimate: 3h00m
+pub fn format_field(task: &Task) -> String {
+    match task.estimate {
+        Some(estimate) => format!(", estimate: {}", timer::format_duration(estimate)),
+        None => String::new(),
+    }
+}
+
+pub fn parse_field(estimate: &str) -> Option<Duration> {
+    timer::parse_duration(estimate).ok()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::timer::Interval;
+    use crate::Deadline;
+
+    fn estimated_task(tasks: &[Task], thing: &str, deadline: &str) -> Task {
+        let deadline = Deadline::new(&deadline.to_string()).unwrap();
+        let mut task = Task::new_task(tasks, thing, deadline);
+        task.estimate = Some(Duration::hours(3));
+        task
+    }
+
+    fn date_switch(args: Vec<&str>) -> Option<Switches> {
+        Some(vec![("--date".to_string(), Some(args))])
+    }
+
+    #[test]
+    fn date_selects_tasks_by_time_tracked_in_the_range() {
+        let mut tasks = vec![];
+
+        // deadline is outside of the range, only the time was tracked today
+        let mut tracked_today = estimated_task(&tasks, "tracked today", "01/01/2099");
+        let now = Task::timestamp();
+        tracked_today.intervals = vec![
+            Interval {
+                start: now - Duration::days(30),
+                end: Some(now - Duration::days(30) + Duration::hours(2)),
+            },
+            Interval {
+                start: now - Duration::minutes(90),
+                end: Some(now),
+            },
+        ];
+        tasks.push(tracked_today);
+
+        let not_tracked = estimated_task(&tasks, "not tracked", "today 23:59");
+        tasks.push(not_tracked);
+
+        let estimated = estimated(&tasks, date_switch(vec!["last-7-days"])).unwrap();
+
+        assert_eq!(estimated.len(), 1);
+        assert_eq!(estimated[0].0.unquoted_thing(), "tracked today");
+        assert_eq!(estimated[0].2, Duration::minutes(90));
+    }
+
+    #[test]
+    fn without_date_every_estimated_task_counts_all_tracked_time() {
+        let mut tasks = vec![];
+
+        let mut tracked = estimated_task(&tasks, "tracked", "01/01/2099");
+        let now = Task::timestamp();
+        tracked.intervals = vec![Interval {
+            start: now - Duration::days(30),
+            end: Some(now - Duration::days(30) + Duration::hours(2)),
+        }];
+        tasks.push(tracked);
+
+        let not_tracked = estimated_task(&tasks, "not tracked", "01/01/2099");
+        tasks.push(not_tracked);
+
+        let estimated = estimated(&tasks, None).unwrap();
+
+        assert_eq!(estimated.len(), 2);
+        assert_eq!(estimated[0].2, Duration::hours(2));
+        assert_eq!(estimated[1].2, Duration::zero());
+    }
+}
@@ -0,0 +1,520 @@
+use crate::tasks_file_manager::{self, format_task_line, parse_task_line};
+use crate::{cli_manager, notes, timer, trash, DateTimeFormatter, Task};
+use chrono::{DateTime, Local, SecondsFormat};
+use std::collections::HashMap;
+
+/// Operation that changed the task
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum ChangeKind {
+    Add,
+    Edit,
+    Expire,
+    Annotate,
+    Attach,
+    Track,
+    Reopen,
+    Revert,
+    Delete,
+    Restore,
+}
+
+impl ChangeKind {
+    pub const ALL: [ChangeKind; 10] = [
+        ChangeKind::Add,
+        ChangeKind::Edit,
+        ChangeKind::Expire,
+        ChangeKind::Annotate,
+        ChangeKind::Attach,
+        ChangeKind::Track,
+        ChangeKind::Reopen,
+        ChangeKind::Revert,
+        ChangeKind::Delete,
+        ChangeKind::Restore,
+    ];
+
+    pub fn name(&self) -> &'static str {
+        match self {
+            ChangeKind::Add => "add",
+            ChangeKind::Edit => "edit",
+            ChangeKind::Expire => "expire",
+            ChangeKind::Annotate => "annotate",
+            ChangeKind::Attach => "attach",
+            ChangeKind::Track => "track",
+            ChangeKind::Reopen => "reopen",
+            ChangeKind::Revert => "revert",
+            ChangeKind::Delete => "delete",
+            ChangeKind::Restore => "restore",
+        }
+    }
+
+    pub fn parse(name: &str) -> Result<Self, String> {
+        match name.trim().to_lowercase().as_str() {
+            "add" => Ok(ChangeKind::Add),
+            "edit" => Ok(ChangeKind::Edit),
+            "expire" => Ok(ChangeKind::Expire),
+            "annotate" => Ok(ChangeKind::Annotate),
+            "attach" => Ok(ChangeKind::Attach),
+            "track" => Ok(ChangeKind::Track),
+            "reopen" => Ok(ChangeKind::Reopen),
+            "revert" => Ok(ChangeKind::Revert),
+            "delete" => Ok(ChangeKind::Delete),
+            "restore" => Ok(ChangeKind::Restore),
+            other => Err(format!("Unknown kind of change {other}")),
+        }
+    }
+}
+
+/// Single change of the task, the task is kept as it was before and after the change
+#[derive(Debug, Clone, PartialEq)]
+pub struct HistoryEntry {
+    // None for entries recorded before the history carried the date and the user
+    pub at: Option<DateTime<Local>>,
+    pub user: Option<String>,
+    pub kind: ChangeKind,
+    pub before: Task,
+    pub after: Task,
+    // why the change was made, given to reopen
+    pub reason: Option<String>,
+}
+
+impl HistoryEntry {
+    pub fn new(kind: ChangeKind, before: Task, after: Task) -> Self {
+        HistoryEntry {
+            at: Some(DateTime::date_now()),
+            user: Some(whoami::username()),
+            kind,
+            before,
+            after,
+            reason: None,
+        }
+    }
+
+    /// Changed fields as (field, before, after)
+    pub fn changes(&self) -> Vec<(&'static str, String, String)> {
+        let (before, after) = (&self.before, &self.after);
+        let mut changes = vec![];
+
+        // restored task gets another label if its label is taken
+        if before.label != after.label {
+            changes.push(("label", before.label.clone(), after.label.clone()));
+        }
+
+        if before.thing != after.thing {
+            changes.push(("thing", before.thing.clone(), after.thing.clone()));
+        }
+
+        if before.status != after.status {
+            changes.push((
+                "status",
+                before.status.to_string(),
+                after.status.to_string(),
+            ));
+        }
+
+        if before.deadline != after.deadline {
+            changes.push((
+                "deadline",
+                DateTime::date_user_formating(before.deadline.date),
+                DateTime::date_user_formating(after.deadline.date),
+            ));
+        }
+
+        // lines of the note are joined, so the change stays on one line
+        if before.note != after.note {
+            let note = |task: &Task| {
+                if task.note.is_empty() {
+                    "none".to_string()
+                } else {
+                    format!("\"{}\"", task.note.lines().collect::<Vec<_>>().join(" / "))
+                }
+            };
+            changes.push(("note", note(before), note(after)));
+        }
+
+        if before.annotations != after.annotations {
+            let annotation = |task: &Task| match task.annotations.last() {
+                Some(annotation) => format!("\"{}\"", annotation.text),
+                None => "none".to_string(),
+            };
+            changes.push(("annotation", annotation(before), annotation(after)));
+        }
+
+        if before.attachments != after.attachments {
+            let attachment = |task: &Task| {
+                task.attachments
+                    .last()
+                    .cloned()
+                    .unwrap_or("none".to_string())
+            };
+            changes.push(("attachment", attachment(before), attachment(after)));
+        }
+
+        if before.estimate != after.estimate {
+            let estimate = |task: &Task| {
+                task.estimate
+                    .map(timer::format_duration)
+                    .unwrap_or("none".to_string())
+            };
+            changes.push(("estimate", estimate(before), estimate(after)));
+        }
+
+        if before.intervals != after.intervals {
+            changes.push((
+                "time tracked",
+                timer::summary(before),
+                timer::summary(after),
+            ));
+        }
+
+        changes
+    }
+
+    /// e.g. 2026-10-18 14:02 alice: status Todo → Postponed(20/10/2026 10:00)
+    pub fn changelog_line(&self) -> String {
+        let at = self
+            .at
+            .map(|at| at.format("%Y-%m-%d %H:%M").to_string())
+            .unwrap_or("unknown date".to_string());
+        let user = self.user.as_deref().unwrap_or("unknown user");
+
+        let kind = match self.kind {
+            ChangeKind::Edit => String::new(),
+            kind => format!(" ({})", kind.name()),
+        };
+
+        format!("{at} {user}{kind}: {}", self.changes_text())
+    }
+
+    /// e.g. 2026-10-18 14:02 alice expire Task 4 "Buy milk": status Todo → Expired(18/10/2026 12:00)
+    pub fn log_line(&self) -> String {
+        format!(
+            "{} {} {} {} {}: {}",
+            self.at
+                .map(|at| at.format("%Y-%m-%d %H:%M").to_string())
+                .unwrap_or("unknown date".to_string()),
+            self.user.as_deref().unwrap_or("unknown user"),
+            self.kind.name(),
+            self.after.label,
+            self.after.thing,
+            self.changes_text()
+        )
+    }
+
+    fn changes_text(&self) -> String {
+        let changes = self
+            .changes()
+            .into_iter()
+            .map(|(field, before, after)| format!("{field} {before} → {after}"))
+            .collect::<Vec<_>>();
+
+        let text = match self.kind {
+            _ if !changes.is_empty() => changes.join(", "),
+            ChangeKind::Add => "task added".to_string(),
+            ChangeKind::Delete => "task deleted".to_string(),
+            ChangeKind::Restore => "task restored".to_string(),
+            _ => "no changed fields".to_string(),
+        };
+
+        match &self.reason {
+            Some(reason) => format!("{text}, reason: {reason}"),
+            None => text,
+        }
+    }
+}
+
+/// Records the change of the task and sets its modification time, nothing is recorded if the task did not change.
+/// Added task is recorded as it was created
+pub fn record(
+    tasks_history: &mut HashMap<String, Vec<HistoryEntry>>,
+    kind: ChangeKind,
+    before: Task,
+    after: &mut Task,
+) {
+    record_with_reason(tasks_history, kind, before, after, None)
+}
+
+/// Same as record, the reason is shown together with the changes in the history and the log
+pub fn record_with_reason(
+    tasks_history: &mut HashMap<String, Vec<HistoryEntry>>,
+    kind: ChangeKind,
+    before: Task,
+    after: &mut Task,
+    reason: Option<&str>,
+) {
+    if before == *after && kind != ChangeKind::Add {
+        return;
+    }
+
+    if before != *after {
+        after.touch();
+    }
+
+    tasks_history
+        .entry(after.label.clone())
+        .or_default()
+        .push(HistoryEntry {
+            reason: reason.map(String::from),
+            ..HistoryEntry::new(kind, before, after.clone())
+        });
+}
+
+/// Restores fields of the task as they were before the change with given number, all fields if none are given.
+/// Versions of another task that got the same label after the deletion are refused
+pub fn revert(
+    tasks: &mut [Task],
+    tasks_history: &mut HashMap<String, Vec<HistoryEntry>>,
+    task_label_number: &str,
+    version: &str,
+    fields: &[&str],
+) -> Result<(), String> {
+    let task = Task::find_by_label_number(tasks, task_label_number)?;
+    let task_labeled_by = task.label.clone();
+
+    let entries = tasks_history
+        .get(&task_labeled_by)
+        .map(Vec::as_slice)
+        .unwrap_or_default();
+
+    let version = version
+        .parse::<usize>()
+        .ok()
+        .filter(|version| (1..=entries.len()).contains(version))
+        .ok_or(format!(
+            "{task_labeled_by} has no version {version}, versions are numbered in show task {task_label_number} --history"
+        ))?;
+
+    // versions saved before tasks had uid are told apart by the later versions of the same label
+    let is_another_task = entries[version - 1..]
+        .iter()
+        .flat_map(|entry| [&entry.before, &entry.after])
+        .any(|version| !version.uid.is_empty() && version.uid != task.uid);
+
+    if is_another_task {
+        return Err(format!(
+            "Version {version} belongs to another task that was labeled as {task_labeled_by} before it was deleted"
+        ));
+    }
+
+    let target = entries[version - 1].before.clone();
+    let prev_task = task.clone();
+    let all = fields.is_empty();
+
+    if all || fields.contains(&"thing") {
+        task.thing = target.thing;
+    }
+
+    if all || fields.contains(&"status") {
+        task.status = target.status;
+    }
+
+    if all || fields.contains(&"deadline") {
+        task.deadline = target.deadline;
+    }
+
+    if *task == prev_task {
+        println!("{task_labeled_by} already matches version {version}");
+        return Ok(());
+    }
+
+    record(tasks_history, ChangeKind::Revert, prev_task, task);
+
+    println!("Reverted task to version {version}:\n{task}");
+
+    tasks_file_manager::save(tasks, tasks_history);
+
+    Ok(())
+}
+
+/// Changes of all tasks, including the deleted ones, the oldest first.
+/// Changes without a date are listed first and left out when --since is given
+pub fn log(
+    tasks_history: &HashMap<String, Vec<HistoryEntry>>,
+    since: Option<&str>,
+    user: Option<&str>,
+    op: Option<&str>,
+) -> Result<(), String> {
+    let since = since
+        .map(|since| match cli_manager::named_range(since) {
+            Some((start, _)) => Ok(start),
+            None => DateTime::parse_formated_string_to_datetime(
+                &since.to_string(),
+                DateTime::date_now(),
+            )
+            .map_err(|err| format!("Could not parse --since date {since}: {err}")),
+        })
+        .transpose()?;
+    let op = op
+        .map(|op| {
+            ChangeKind::parse(op).map_err(|_| {
+                let names = ChangeKind::ALL.map(|kind| kind.name()).join(" | ");
+                format!("Unknown operation {op}, expected: {names}")
+            })
+        })
+        .transpose()?;
+
+    let mut labels = tasks_history.keys().collect::<Vec<_>>();
+    labels.sort_by_key(|label| (label.len(), *label));
+
+    let deleted = trash::histories()?;
+
+    let mut entries = labels
+        .into_iter()
+        .flat_map(|label| &tasks_history[label])
+        .chain(deleted.iter().flatten())
+        .filter(|entry| match since {
+            Some(since) => entry.at.is_some_and(|at| at >= since),
+            None => true,
+        })
+        .filter(|entry| match user {
+            Some(user) => entry
+                .user
+                .as_deref()
+                .is_some_and(|x| x.eq_ignore_ascii_case(user)),
+            None => true,
+        })
+        .filter(|entry| match op {
+            Some(op) => entry.kind == op,
+            None => true,
+        })
+        .collect::<Vec<_>>();
+
+    // entries of one task keep their order, dates of the legacy ones are unknown
+    entries.sort_by_key(|entry| entry.at);
+    // restored tasks can be left in the trash after undo of the deletion
+    entries.dedup();
+
+    if entries.is_empty() {
+        println!("No changes found");
+        return Ok(());
+    }
+
+    for entry in entries {
+        println!("{}", entry.log_line());
+    }
+
+    Ok(())
+}
+
+/// Previous versions of the task, the oldest first
+pub fn versions(entries: &[HistoryEntry]) -> Vec<Task> {
+    entries.iter().map(|entry| entry.before.clone()).collect()
+}
+
+// History entry is stored on one line:
+// Change { at: 2026-10-18T14:02:00+02:00, user: alice, kind: edit } before: Task { ... } after: Task { ... }
+pub fn format_entry_line(entry: &HistoryEntry) -> String {
+    // entries without the reason are written as before it was kept
+    let reason = entry
+        .reason
+        .as_ref()
+        .map(|reason| format!(", reason: {}", notes::encode(reason)))
+        .unwrap_or_default();
+
+    format!(
+        "Change {{ at: {}, user: {}, kind: {}{reason} }} before: {} after: {}",
+        entry
+            .at
+            .map(|at| at.to_rfc3339_opts(SecondsFormat::Secs, false))
+            .unwrap_or("unknown".to_string()),
+        entry.user.as_deref().unwrap_or("unknown"),
+        entry.kind.name(),
+        format_task_line(&entry.before),
+        format_task_line(&entry.after),
+    )
+}
+
+/// Line written by format_entry_line, or a bare task line of the history without dates and users.
+/// Task after the change of the bare line is not known until link_legacy is called
+pub fn parse_entry_line(line: &str) -> Result<HistoryEntry, String> {
+    if line.starts_with("Task { ") {
+        let task = parse_task_line(line);
+
+        return Ok(HistoryEntry {
+            at: None,
+            user: None,
+            kind: ChangeKind::Edit,
+            before: task.clone(),
+            after: task,
+            reason: None,
+        });
+    }
+
+    let invalid = || format!("Invalid history entry: {line}");
+
+    let (header, tasks) = line
+        .strip_prefix("Change { ")
+        .and_then(|line| line.split_once(" } before: "))
+        .ok_or_else(invalid)?;
+    let (before, after) = tasks.rsplit_once(" after: ").ok_or_else(invalid)?;
+
+    let mut entry = HistoryEntry {
+        at: None,
+        user: None,
+        kind: ChangeKind::Edit,
+        before: parse_task_line(before),
+        after: parse_task_line(after),
+        reason: None,
+    };
+
+    for field in header.split(", ") {
+        match field.split_once(": ") {
+            Some((_, "unknown")) => (),
+            Some(("at", at)) => {
+                entry.at = Some(
+                    DateTime::parse_from_rfc3339(at)
+                        .map_err(|err| format!("{err}: {line}"))?
+                        .with_timezone(&Local),
+                )
+            }
+            Some(("user", user)) => entry.user = Some(user.to_string()),
+            Some(("kind", kind)) => entry.kind = ChangeKind::parse(kind)?,
+            Some(("reason", reason)) => entry.reason = Some(notes::decode(reason)),
+            _ => return Err(invalid()),
+        }
+    }
+
+    Ok(entry)
+}
+
+/// Bare task lines of the history only hold the version before the change,
+/// the version after it is the next one in the history or the current task.
+/// Versions that were recorded without any change are left out
+pub fn link_legacy(entries: &mut Vec<HistoryEntry>, current: Option<&Task>) {
+    for idx in 0..entries.len() {
+        if entries[idx].at.is_some() || entries[idx].before != entries[idx].after {
+            continue;
+        }
+
+        let next = match entries.get(idx + 1) {
+            Some(next) => Some(next.before.clone()),
+            None => current.cloned(),
+        };
+
+        if let Some(next) = next {
+            entries[idx].after = next;
+        }
+    }
+
+    entries.retain(|entry| entry.at.is_some() || entry.before != entry.after);
+}
+
+/// Entries grouped by the label of the task, bare task lines are linked to the current tasks
+pub fn group_by_label(
+    entries: Vec<HistoryEntry>,
+    tasks: &[Task],
+) -> HashMap<String, Vec<HistoryEntry>> {
+    let mut tasks_history: HashMap<String, Vec<HistoryEntry>> = HashMap::new();
+
+    for entry in entries {
+        tasks_history
+            .entry(entry.after.label.clone())
+            .or_default()
+            .push(entry);
+    }
+
+    for (label, entries) in tasks_history.iter_mut() {
+        link_legacy(entries, tasks.iter().find(|task| task.label == *label));
+    }
+
+    tasks_history
+}
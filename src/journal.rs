@@ -0,0 +1,401 @@
+use crate::history::{self, ChangeKind, HistoryEntry};
+use crate::tasks_file_manager::{self, format_task_line, parse_task_line};
+use crate::{trash, Task};
+use std::collections::HashMap;
+use std::error::Error;
+use std::fs::{self, OpenOptions};
+use std::io::Write;
+
+const JOURNAL_FILE: &str = "tasks_journal.txt";
+
+// older operations are forgotten
+const MAX_OPERATIONS: usize = 100;
+
+/// Task under one label together with its previous versions, task is None if label is not used
+#[derive(Debug, Clone, Default, PartialEq)]
+struct Snapshot {
+    task: Option<Task>,
+    history: Vec<HistoryEntry>,
+}
+
+#[derive(Debug, Clone)]
+struct Change {
+    label: String,
+    before: Snapshot,
+    after: Snapshot,
+}
+
+/// Command that changed tasks, undone operations can be redone until another change is recorded
+#[derive(Debug, Clone)]
+struct Operation {
+    name: String,
+    undone: bool,
+    changes: Vec<Change>,
+}
+
+/// Commands that move through the journal or record their changes one by one, their run is not recorded as a whole
+pub fn is_journal_command(words: &[&str]) -> bool {
+    matches!(
+        words.first().map(|word| word.to_lowercase()).as_deref(),
+        Some("undo" | "redo" | "tui")
+    )
+}
+
+/// Number of operations to undo or redo, 1 if not supplied
+pub fn parse_count(args: &[&str]) -> Result<usize, String> {
+    match args {
+        [] => Ok(1),
+        [count] => count
+            .parse::<usize>()
+            .ok()
+            .filter(|count| *count > 0)
+            .ok_or(format!(
+                "Expected positive number of operations, got: {count}"
+            )),
+        _ => Err("Expected single number of operations".to_string()),
+    }
+}
+
+/// Records what the operation changed, comparing tasks with their state before it. Nothing is recorded if tasks did not change
+pub fn record(
+    name: &str,
+    before: &(Vec<Task>, HashMap<String, Vec<HistoryEntry>>),
+    tasks: &[Task],
+    tasks_history: &HashMap<String, Vec<HistoryEntry>>,
+) -> Result<(), Box<dyn Error>> {
+    let (before_tasks, before_history) = before;
+
+    let mut labels = before_tasks
+        .iter()
+        .chain(tasks)
+        .map(|task| &task.label)
+        .chain(before_history.keys())
+        .chain(tasks_history.keys())
+        .collect::<Vec<_>>();
+    labels.sort();
+    labels.dedup();
+
+    let changes = labels
+        .into_iter()
+        .filter_map(|label| {
+            let before = snapshot(label, before_tasks, before_history);
+            let after = snapshot(label, tasks, tasks_history);
+
+            (before != after).then(|| Change {
+                label: label.clone(),
+                before,
+                after,
+            })
+        })
+        .collect::<Vec<_>>();
+
+    if changes.is_empty() {
+        return Ok(());
+    }
+
+    let mut operations = load()?;
+
+    // new change makes the undone operations unreachable
+    operations.retain(|operation| !operation.undone);
+    operations.push(Operation {
+        name: name.trim().to_string(),
+        undone: false,
+        changes,
+    });
+
+    if operations.len() > MAX_OPERATIONS {
+        operations.drain(..operations.len() - MAX_OPERATIONS);
+    }
+
+    save(&operations)
+}
+
+/// Reverts last count operations, including deleted tasks and their history
+pub fn undo(
+    tasks: &mut Vec<Task>,
+    tasks_history: &mut HashMap<String, Vec<HistoryEntry>>,
+    count: usize,
+) -> Result<(), String> {
+    let mut operations = load().map_err(|err| err.to_string())?;
+
+    if operations.iter().all(|operation| operation.undone) {
+        return Err("Nothing to undo".to_string());
+    }
+
+    let mut result = Ok(());
+
+    for operation in operations
+        .iter_mut()
+        .rev()
+        .filter(|operation| !operation.undone)
+        .take(count)
+    {
+        if let Err(err) = apply(tasks, tasks_history, operation, true) {
+            result = Err(err);
+            break;
+        }
+
+        operation.undone = true;
+        println!("Undone: {}", operation.name);
+    }
+
+    // operations applied before the failure are kept
+    persist(tasks, tasks_history, &operations);
+
+    result
+}
+
+/// Applies again last count undone operations
+pub fn redo(
+    tasks: &mut Vec<Task>,
+    tasks_history: &mut HashMap<String, Vec<HistoryEntry>>,
+    count: usize,
+) -> Result<(), String> {
+    let mut operations = load().map_err(|err| err.to_string())?;
+
+    if operations.iter().all(|operation| !operation.undone) {
+        return Err("Nothing to redo".to_string());
+    }
+
+    let mut result = Ok(());
+
+    for operation in operations
+        .iter_mut()
+        .filter(|operation| operation.undone)
+        .take(count)
+    {
+        if let Err(err) = apply(tasks, tasks_history, operation, false) {
+            result = Err(err);
+            break;
+        }
+
+        operation.undone = false;
+        println!("Redone: {}", operation.name);
+    }
+
+    // operations applied before the failure are kept
+    persist(tasks, tasks_history, &operations);
+
+    result
+}
+
+fn snapshot(
+    label: &str,
+    tasks: &[Task],
+    tasks_history: &HashMap<String, Vec<HistoryEntry>>,
+) -> Snapshot {
+    Snapshot {
+        task: tasks.iter().find(|task| task.label == label).cloned(),
+        history: tasks_history.get(label).cloned().unwrap_or_default(),
+    }
+}
+
+/// Puts tasks into the state before (undo) or after (redo) the operation
+fn apply(
+    tasks: &mut Vec<Task>,
+    tasks_history: &mut HashMap<String, Vec<HistoryEntry>>,
+    operation: &Operation,
+    undo: bool,
+) -> Result<(), String> {
+    // labels could be reused only by changes made outside of the journal
+    for change in &operation.changes {
+        let current = if undo { &change.after } else { &change.before };
+
+        let is_used = tasks.iter().any(|task| task.label == change.label);
+        if is_used != current.task.is_some() {
+            let reason = if is_used {
+                "is used by another task"
+            } else {
+                "does not exist anymore"
+            };
+
+            return Err(format!(
+                "Cannot restore \"{}\", {} {reason}",
+                operation.name, change.label
+            ));
+        }
+    }
+
+    for change in &operation.changes {
+        let current = if undo { &change.after } else { &change.before };
+        let state = if undo { &change.before } else { &change.after };
+
+        move_through_trash(current, state, undo)?;
+
+        match &state.task {
+            Some(task) => match tasks.iter_mut().find(|x| x.label == change.label) {
+                Some(current) => *current = task.clone(),
+                None => {
+                    // keep tasks ordered by id
+                    let idx = tasks
+                        .iter()
+                        .position(|x| x.id() > task.id())
+                        .unwrap_or(tasks.len());
+                    tasks.insert(idx, task.clone());
+                }
+            },
+            None => tasks.retain(|x| x.label != change.label),
+        }
+
+        if state.history.is_empty() {
+            tasks_history.remove(&change.label);
+        } else {
+            tasks_history.insert(change.label.clone(), state.history.clone());
+        }
+    }
+
+    Ok(())
+}
+
+fn is_restored(snapshot: &Snapshot) -> bool {
+    snapshot
+        .history
+        .last()
+        .is_some_and(|entry| entry.kind == ChangeKind::Restore)
+}
+
+/// Deleted tasks are kept in the trash, so undoing or redoing the deletion or the restoration moves them in or out of it
+fn move_through_trash(current: &Snapshot, state: &Snapshot, undo: bool) -> Result<(), String> {
+    match (&current.task, &state.task) {
+        // restoration is undone
+        (Some(task), None) if undo && is_restored(current) => {
+            trash::put_back(task.clone(), current.history.clone())
+        }
+        // deletion is redone
+        (Some(task), None) if !undo => {
+            trash::put(vec![(task.clone(), current.history.clone())]).map_err(|err| err.to_string())
+        }
+        // deletion is undone or restoration is redone
+        (None, Some(task)) if undo || is_restored(state) => trash::take(task),
+        _ => Ok(()),
+    }
+}
+
+fn persist(
+    tasks: &[Task],
+    tasks_history: &mut HashMap<String, Vec<HistoryEntry>>,
+    operations: &[Operation],
+) {
+    tasks_file_manager::save(tasks, tasks_history);
+
+    if let Err(err) = save(operations) {
+        eprintln!("{err}");
+    }
+}
+
+// Journal file consists of operations followed by their changes:
+// Operation { undone: false, name: delete 4 }
+// Change { label: Task 4 }
+// before: Task { thing: "buy milk", status: Todo, label: Task 4, deadline: Deadline { date: 2023-06-10T12:30:00+02:00 } }
+// before history: Change { ... } before: Task { ... } after: Task { ... }
+// after: Task { ... }
+// after history: Change { ... }
+fn save(operations: &[Operation]) -> Result<(), Box<dyn Error>> {
+    let file_path = tasks_file_manager::make_file_path_in_working_dir(JOURNAL_FILE)?;
+
+    let mut file = OpenOptions::new()
+        .write(true)
+        .truncate(true)
+        .create(true)
+        .open(file_path)?;
+
+    for operation in operations {
+        writeln!(
+            file,
+            "Operation {{ undone: {}, name: {} }}",
+            operation.undone, operation.name
+        )?;
+
+        for change in &operation.changes {
+            writeln!(file, "Change {{ label: {} }}", change.label)?;
+
+            for (prefix, state) in [("before", &change.before), ("after", &change.after)] {
+                if let Some(task) = &state.task {
+                    writeln!(file, "{prefix}: {}", format_task_line(task))?;
+                }
+                for entry in &state.history {
+                    writeln!(
+                        file,
+                        "{prefix} history: {}",
+                        history::format_entry_line(entry)
+                    )?;
+                }
+            }
+        }
+    }
+
+    Ok(())
+}
+
+fn load() -> Result<Vec<Operation>, Box<dyn Error>> {
+    let file_path = tasks_file_manager::make_file_path_in_working_dir(JOURNAL_FILE)?;
+
+    if !file_path.exists() {
+        return Ok(vec![]);
+    }
+
+    let mut operations: Vec<Operation> = vec![];
+
+    for line in fs::read_to_string(file_path)?.lines() {
+        if let Some(operation) = line
+            .strip_prefix("Operation { undone: ")
+            .and_then(|line| line.strip_suffix(" }"))
+        {
+            let (undone, name) = operation
+                .split_once(", name: ")
+                .ok_or(format!("Invalid operation in the journal: {line}"))?;
+
+            operations.push(Operation {
+                name: name.to_string(),
+                undone: undone == "true",
+                changes: vec![],
+            });
+            continue;
+        }
+
+        let operation = operations
+            .last_mut()
+            .ok_or(format!("Change without operation in the journal: {line}"))?;
+
+        if let Some(label) = line
+            .strip_prefix("Change { label: ")
+            .and_then(|line| line.strip_suffix(" }"))
+        {
+            operation.changes.push(Change {
+                label: label.to_string(),
+                before: Snapshot::default(),
+                after: Snapshot::default(),
+            });
+            continue;
+        }
+
+        let change = operation
+            .changes
+            .last_mut()
+            .ok_or(format!("Task without change in the journal: {line}"))?;
+
+        match line.split_once(": ") {
+            Some(("before", task)) => change.before.task = Some(parse_task_line(task)),
+            Some(("before history", entry)) => change
+                .before
+                .history
+                .push(history::parse_entry_line(entry)?),
+            Some(("after", task)) => change.after.task = Some(parse_task_line(task)),
+            Some(("after history", entry)) => {
+                change.after.history.push(history::parse_entry_line(entry)?)
+            }
+            _ => return Err(format!("Invalid line in the journal: {line}").into()),
+        }
+    }
+
+    for change in operations
+        .iter_mut()
+        .flat_map(|operation| operation.changes.iter_mut())
+    {
+        for state in [&mut change.before, &mut change.after] {
+            history::link_legacy(&mut state.history, state.task.as_ref());
+        }
+    }
+
+    Ok(operations)
+}
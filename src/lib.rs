@@ -8,23 +8,57 @@ use std::collections::HashMap;
 use std::error::Error;
 use std::fs;
 use std::fs::OpenOptions;
-use std::io::{self, BufRead, BufReader, Write};
+use std::io::{self, BufRead, BufReader, IsTerminal, Write};
 use std::path::Path;
 use std::path::PathBuf;
+use std::sync::atomic::{AtomicU32, Ordering};
 use std::{fmt, vec};
 
+mod attachments;
+mod commands;
+mod editor;
+mod estimates;
+mod history;
+mod journal;
+mod notes;
+mod output_format;
+mod prompt;
+mod timer;
+mod trash;
+mod tui;
 mod utils;
 
-#[derive(Debug, Clone)]
+use cli_manager::Switches;
+use commands::CommandSpec;
+use history::ChangeKind;
+pub use history::HistoryEntry;
+
+pub use output_format::OutputFormat;
+
+#[derive(Debug, Clone, PartialEq)]
 pub struct Task {
     thing: String,
     status: TaskStatus,
     deadline: Deadline,
     pub label: String,
+    // stays the same when the label of deleted task is given to another one, empty for versions saved before it existed
+    uid: String,
+    // None for tasks saved before the timestamps were recorded
+    created: Option<DateTime<Local>>,
+    modified: Option<DateTime<Local>>,
+    // multi-line text, unlike the thing it is not shown in the list of tasks
+    note: String,
+    annotations: Vec<notes::Annotation>,
+    // absolute paths of local files and URLs
+    attachments: Vec<String>,
+    // work done on the task, the running timer is the interval without end
+    intervals: Vec<timer::Interval>,
+    // expected time to work on the task, compared with the tracked time by report estimates
+    estimate: Option<Duration>,
 }
 
 impl Task {
-    fn add_task(tasks: &mut Vec<Task>) {
+    fn add_task(tasks: &mut Vec<Task>, tasks_history: &mut HashMap<String, Vec<HistoryEntry>>) {
         cli_manager::clear_console();
         println!("Type \"exit\" to break to the CLI user interface");
         println!("{}{}", "Thing: String\n", 
@@ -62,159 +96,539 @@ impl Task {
                 break;
             }
 
-            let mut all_ids = Task::get_all_ids(&tasks);
+            Task::insert_task(tasks, tasks_history, &thing, parsed_deadline, None);
+        }
+    }
 
-            let available_ids = Task::find_available_ids(&mut all_ids);
+    /// Creates task with the first available label and saves it, the addition is recorded in the history
+    fn insert_task(
+        tasks: &mut Vec<Task>,
+        tasks_history: &mut HashMap<String, Vec<HistoryEntry>>,
+        thing: &str,
+        deadline: Deadline,
+        estimate: Option<Duration>,
+    ) -> Task {
+        let mut task = Task::new_task(tasks, thing, deadline);
+        task.estimate = estimate;
 
-            let label = if !available_ids.is_empty() {
-                format!("Task {}", available_ids.first().unwrap())
-            } else {
-                format!("Task {}", tasks.len() + 1)
-            };
+        println!(
+            "Task successfully added:\nTask {{ thing: {}, status: {:?}, deadline: {} }}",
+            task.thing,
+            task.status,
+            DateTime::date_user_formating(task.deadline.date)
+        );
 
-            let task = Task {
-                thing: format!("\"{}\"", thing),
-                status: TaskStatus::Todo,
-                deadline: parsed_deadline,
-                label,
-            };
+        tasks.push(task.clone());
+        history::record(tasks_history, ChangeKind::Add, task.clone(), &mut task);
 
-            println!(
-                "Task successfully added:\nTask {{ thing: {}, status: {:?}, deadline: {} }}",
-                task.thing,
-                task.status,
-                DateTime::date_user_formating(task.deadline.date)
-            );
+        tasks_file_manager::save(tasks, tasks_history);
 
-            tasks.push(task);
+        task
+    }
 
-            if let Err(err) = tasks_file_manager::save_tasks(&tasks) {
-                eprint!("{err}");
-            };
+    /// Task labeled by the first available id, it is not added to tasks
+    fn new_task(tasks: &[Task], thing: &str, deadline: Deadline) -> Task {
+        let mut all_ids = Task::get_all_ids(tasks);
+
+        let available_ids = Task::find_available_ids(&mut all_ids);
+
+        let label = match available_ids.first() {
+            Some(id) => format!("Task {id}"),
+            None => format!("Task {}", tasks.len() + 1),
+        };
+
+        let mut task = Task {
+            thing: format!("\"{}\"", thing),
+            status: TaskStatus::Todo,
+            deadline,
+            label,
+            uid: Task::new_uid(),
+            created: None,
+            modified: None,
+            note: String::new(),
+            annotations: vec![],
+            attachments: vec![],
+            intervals: vec![],
+            estimate: None,
+        };
+        task.mark_created();
+
+        task
+    }
+
+    /// Adds task parsed from redirected output as if it was created now, with its own uid and the addition in the history
+    pub fn import_task(
+        tasks: &mut Vec<Task>,
+        tasks_history: &mut HashMap<String, Vec<HistoryEntry>>,
+        mut task: Task,
+    ) {
+        task.uid = Task::new_uid();
+        task.mark_created();
+
+        tasks.push(task.clone());
+        history::record(tasks_history, ChangeKind::Add, task.clone(), &mut task);
+    }
+
+    /// Sets the creation and modification time of new or imported task to now
+    pub fn mark_created(&mut self) {
+        let now = Task::timestamp();
+
+        self.created = Some(now);
+        self.modified = Some(now);
+    }
+
+    /// Sets the modification time to now
+    fn touch(&mut self) {
+        self.modified = Some(Task::timestamp());
+    }
+
+    /// Current time in whole seconds, the precision of the tasks file
+    fn timestamp() -> DateTime<Local> {
+        let now = DateTime::date_now();
+        now.with_nanosecond(0).unwrap_or(now)
+    }
+
+    /// Unique enough for tasks of one user, made of the current time and a counter of this run
+    fn new_uid() -> String {
+        static COUNTER: AtomicU32 = AtomicU32::new(0);
+
+        let nanos = std::time::SystemTime::now()
+            .duration_since(std::time::UNIX_EPOCH)
+            .map(|duration| duration.as_nanos())
+            .unwrap_or_default();
+
+        format!("{nanos:x}{:x}", COUNTER.fetch_add(1, Ordering::Relaxed))
+    }
+
+    /// Task labeled by given number, e.g. 4 for Task 4
+    pub(crate) fn find_by_label_number<'a>(
+        tasks: &'a mut [Task],
+        task_label_number: &str,
+    ) -> Result<&'a mut Task, String> {
+        let task_labeled_by = format!("Task {task_label_number}");
+
+        tasks
+            .iter_mut()
+            .find(|x| x.label == task_labeled_by)
+            .ok_or("Could not found Task with this label".to_string())
+    }
+
+    /// Tasks saved before they had uid get one
+    pub fn assign_missing_uids(tasks: &mut [Task]) {
+        for task in tasks.iter_mut().filter(|task| task.uid.is_empty()) {
+            task.uid = Task::new_uid();
         }
     }
 
     fn edit_task(
-        tasks: &mut Vec<Task>,
+        tasks: &mut [Task],
         task_label_number: String,
         switch_field: &Option<String>,
-        tasks_history: &mut HashMap<String, Vec<Task>>,
+        tasks_history: &mut HashMap<String, Vec<HistoryEntry>>,
     ) -> Result<(), String> {
-        let task_labeled_by = format!("Task {task_label_number}");
         let mut is_switch_invalid = false;
 
-        if let Some(task) = tasks.into_iter().find(|x| x.label == task_labeled_by) {
-            match task.status {
-                TaskStatus::Aborted(_) => {
-                    return Err("Cannot edit task with previous status as Aborted".to_string());
-                }
-                TaskStatus::Expired(_) => {
-                    return Err("Cannot edit task with previous status as Expired".to_string());
-                }
-                _ => loop {
-                    let mut field_to_edit: String = String::new();
-
-                    if !is_switch_invalid {
-                        if let Some(switch) = switch_field {
-                            let switch = switch.trim_start_matches("--").to_string();
-                            match switch.as_str() {
-                                "thing" | "status" => field_to_edit = switch,
-                                _ => {
-                                    eprintln!("No such field to edit, you lying son of a bitch!");
-                                    is_switch_invalid = true;
-                                }
-                            }
-                        };
-                    }
+        let task = Task::find_by_label_number(tasks, &task_label_number)?;
 
-                    if field_to_edit.is_empty() {
-                        field_to_edit = cli_manager::get_labeled_input_from_user("field")
-                            .to_lowercase()
-                            .to_string()
+        // deadline of expired task can be moved to revive it
+        if switch_field.as_deref() == Some("--deadline") {
+            task.check_deadline_editable()?;
+        } else {
+            task.check_editable()?;
+        }
+
+        loop {
+            let mut field_to_edit: String = String::new();
+
+            if !is_switch_invalid {
+                if let Some(switch) = switch_field {
+                    let switch = switch.trim_start_matches("--").to_string();
+                    match switch.as_str() {
+                        "thing" | "status" | "deadline" => field_to_edit = switch,
+                        _ => {
+                            eprintln!("No such field to edit, you lying son of a bitch!");
+                            is_switch_invalid = true;
+                        }
                     }
+                };
+            }
 
-                    let mut prev_task: Option<Task> = None;
+            if field_to_edit.is_empty() {
+                field_to_edit = cli_manager::get_labeled_input_from_user("field")
+                    .to_lowercase()
+                    .to_string()
+            }
 
-                    if field_to_edit == "thing" || field_to_edit == "status" {
-                        prev_task = Some(task.clone());
-                    }
+            let is_field = ["thing", "status", "deadline"].contains(&field_to_edit.as_str());
 
-                    if field_to_edit == "thing" {
-                        EditTaskConfig::edit_thing(task)
-                    } else if field_to_edit == "status" {
-                        EditTaskConfig::edit_status(task)
-                    } else if field_to_edit.to_lowercase() == "exit" {
-                        cli_manager::clear_console_and_display_help();
-                        break;
-                    } else {
-                        eprintln!("No such field to edit, you lying son of a bitch!");
-                        continue;
-                    }
+            let mut prev_task: Option<Task> = None;
 
-                    if field_to_edit == "thing" || field_to_edit == "status" {
-                        if let Some(prev_task) = prev_task {
-                            tasks_history
-                                .entry(task.label.clone())
-                                .or_default()
-                                .push(prev_task);
-                        }
+            if is_field {
+                prev_task = Some(task.clone());
+            }
 
-                        if let Err(err) =
-                            tasks_file_manager::save_tasks_history(tasks, tasks_history)
-                        {
-                            eprintln!("{err}");
-                        };
-                    }
+            if field_to_edit == "thing" {
+                EditTaskConfig::edit_thing(task)
+            } else if field_to_edit == "status" {
+                EditTaskConfig::edit_status(task)
+            } else if field_to_edit == "deadline" {
+                EditTaskConfig::edit_deadline(task)
+            } else if field_to_edit.to_lowercase() == "exit" {
+                cli_manager::clear_console_and_display_help();
+                break;
+            } else {
+                eprintln!("No such field to edit, you lying son of a bitch!");
+                continue;
+            }
 
-                    if let Err(err) = tasks_file_manager::save_tasks(&tasks) {
-                        eprintln!("{err}");
-                    };
+            if is_field {
+                // nothing is recorded if the edit was left with exit
+                if let Some(prev_task) = prev_task {
+                    history::record(tasks_history, ChangeKind::Edit, prev_task, task);
+                }
 
-                    break;
-                },
+                if let Err(err) = tasks_file_manager::save_tasks_history(tasks, tasks_history) {
+                    eprintln!("{err}");
+                };
             }
+
+            if let Err(err) = tasks_file_manager::save_tasks(tasks) {
+                eprintln!("{err}");
+            };
+
+            break;
+        }
+
+        Ok(())
+    }
+
+    fn check_editable(&self) -> Result<(), String> {
+        match self.status {
+            TaskStatus::Aborted(_) => Err(
+                "Cannot edit task with previous status as Aborted, use reopen to make it Todo again"
+                    .to_string(),
+            ),
+            TaskStatus::Expired(_) => Err(
+                "Cannot edit task with previous status as Expired, use reopen to make it Todo again"
+                    .to_string(),
+            ),
+            _ => Ok(()),
+        }
+    }
+
+    /// Deadline can be changed for expired tasks too, moving it to the future makes the task Todo again
+    fn check_deadline_editable(&self) -> Result<(), String> {
+        match self.status {
+            TaskStatus::Aborted(_) => {
+                Err("Cannot edit task with previous status as Aborted".to_string())
+            }
+            _ => Ok(()),
+        }
+    }
+
+    /// Moves the deadline, unlike postponing the status stays untouched unless the task was expired
+    fn set_deadline(&mut self, deadline: Deadline) -> Result<(), String> {
+        if let TaskStatus::Expired(_) = self.status {
+            if deadline.date <= DateTime::date_now() {
+                return Err("Deadline of expired task has to be moved to the future".to_string());
+            }
+
+            self.status = TaskStatus::Todo;
+        }
+
+        self.deadline = deadline;
+
+        Ok(())
+    }
+
+    /// Non interactive counterpart of edit_task, fields that are None are left untouched
+    fn update_task(
+        tasks: &mut [Task],
+        tasks_history: &mut HashMap<String, Vec<HistoryEntry>>,
+        task_label_number: &str,
+        thing: Option<&str>,
+        status: Option<&str>,
+        deadline: Option<&str>,
+    ) -> Result<(), String> {
+        let task = Task::find_by_label_number(tasks, task_label_number)?;
+
+        task.apply_update(tasks_history, thing, status, deadline)?;
+
+        println!("Updated task:\n{task}");
+
+        tasks_file_manager::save(tasks, tasks_history);
+
+        Ok(())
+    }
+
+    /// Makes expired or aborted task Todo again, the resulting deadline has to be in the future so it does not expire right away
+    fn reopen_task(
+        tasks: &mut [Task],
+        tasks_history: &mut HashMap<String, Vec<HistoryEntry>>,
+        task_label_number: &str,
+        deadline: Option<&str>,
+        reason: &str,
+    ) -> Result<(), String> {
+        let task = Task::find_by_label_number(tasks, task_label_number)?;
+        let task_labeled_by = task.label.clone();
+
+        if !matches!(task.status, TaskStatus::Expired(_) | TaskStatus::Aborted(_)) {
+            return Err(format!(
+                "Only expired or aborted tasks can be reopened, {task_labeled_by} is {}",
+                task.status
+            ));
+        }
+
+        let deadline = match deadline {
+            Some(deadline) => Deadline::new(&deadline.trim().to_string())?,
+            None => task.deadline,
+        };
+
+        if deadline.date <= DateTime::date_now() {
+            return Err(format!(
+                "Deadline {} already passed, supply a new one with --deadline",
+                DateTime::date_user_formating(deadline.date)
+            ));
+        }
+
+        let reason = reason.trim();
+
+        if reason.is_empty() {
+            return Err("Reason of reopening cannot be empty".to_string());
+        }
+
+        let prev_task = task.clone();
+
+        task.status = TaskStatus::Todo;
+        task.deadline = deadline;
+
+        history::record_with_reason(
+            tasks_history,
+            ChangeKind::Reopen,
+            prev_task,
+            task,
+            Some(reason),
+        );
+
+        println!("Reopened task ({reason}):\n{task}");
+
+        tasks_file_manager::save(tasks, tasks_history);
+
+        Ok(())
+    }
+
+    /// Changes given fields of the task and records its previous version in the history
+    fn apply_update(
+        &mut self,
+        tasks_history: &mut HashMap<String, Vec<HistoryEntry>>,
+        thing: Option<&str>,
+        status: Option<&str>,
+        deadline: Option<&str>,
+    ) -> Result<(), String> {
+        if thing.is_none() && status.is_none() && deadline.is_some() {
+            self.check_deadline_editable()?;
         } else {
-            return Err("Could not found Task with this label".to_string());
+            self.check_editable()?;
         }
+
+        if thing.is_some_and(|thing| thing.trim().is_empty()) {
+            return Err("Task thing cannot be empty".to_string());
+        }
+
+        let status = status.map(|status| self.parse_status(status)).transpose()?;
+        let deadline = deadline
+            .map(|deadline| Deadline::new(&deadline.trim().to_string()))
+            .transpose()?;
+
+        let prev_task = self.clone();
+
+        if let Some(deadline) = deadline {
+            self.set_deadline(deadline)?;
+        }
+
+        if let Some(thing) = thing {
+            self.thing = format!("\"{}\"", thing);
+        }
+
+        if let Some(status) = status {
+            self.status = status;
+        }
+
+        history::record(tasks_history, ChangeKind::Edit, prev_task, self);
+
         Ok(())
     }
 
+    /// Status as typed by the user: completed | todo | aborted | postponed <date relative to the previous date>
+    fn parse_status(&self, input: &str) -> Result<TaskStatus, String> {
+        use TaskStatus::{Aborted, Completed, Postponed, Todo};
+
+        let input = input.trim().to_lowercase();
+
+        match input.as_str() {
+            // completing completed task again keeps the date of completion
+            "completed" => match self.status {
+                Completed(date) => Ok(Completed(date)),
+                _ => Ok(Completed(DateTime::date_now())),
+            },
+            "todo" => Ok(Todo),
+            "aborted" => Ok(Aborted(DateTime::date_now())),
+            input if input.starts_with("postponed ") => {
+                let date_part = input.trim_start_matches("postponed ").trim().to_string();
+
+                let date_from = match self.status {
+                    Postponed(date) => date,
+                    _ => self.deadline.date,
+                };
+
+                DateTime::parse_formated_string_to_datetime(&date_part, date_from)
+                    .map(Postponed)
+                    .map_err(|err| format!("Could not parse the postponed date: {err}"))
+            }
+            _ => Err("No such status available".to_string()),
+        }
+    }
+
+    /// Moves task or all tasks to the trash, deleting all tasks has to be confirmed unless assume_yes
     fn delete_task(
         tasks: &mut Vec<Task>,
-        tasks_history: &mut HashMap<String, Vec<Task>>,
+        tasks_history: &mut HashMap<String, Vec<HistoryEntry>>,
         task_label_number: String,
+        assume_yes: bool,
     ) -> Result<(), String> {
-        match task_label_number.as_str() {
+        let deleted = match task_label_number.as_str() {
             "all" => {
-                tasks.clear();
+                if tasks.is_empty() {
+                    println!("There are no tasks to delete");
+                    return Ok(());
+                }
+
+                if !assume_yes
+                    && !cli_manager::confirm(&format!("Delete all {} tasks?", tasks.len()))
+                {
+                    println!("Nothing was changed");
+                    return Ok(());
+                }
+
+                let deleted = std::mem::take(tasks);
                 println!("Successfully deleted all tasks");
+                deleted
             }
             task_number => {
                 let task_labeled_by = format!("Task {task_number}");
-                if tasks.iter().any(|x| x.label == task_labeled_by) {
-                    tasks.retain(|x| *x.label != task_labeled_by);
-                    tasks_history.remove(&task_labeled_by);
-                    println!("{task_labeled_by} successfully deleted")
-                } else {
-                    eprintln!("Task with label {task_labeled_by} does not exists");
-                }
+                let idx = tasks
+                    .iter()
+                    .position(|x| x.label == task_labeled_by)
+                    .ok_or(format!("Task with label {task_labeled_by} does not exists"))?;
+
+                println!("{task_labeled_by} successfully deleted");
+                vec![tasks.remove(idx)]
             }
         };
 
-        if let Err(err) = tasks_file_manager::save_tasks(&tasks) {
+        if let Err(err) = Task::trash_deleted(deleted, tasks_history) {
             eprintln!("{err}");
         }
 
-        if let Err(err) = tasks_file_manager::save_tasks_history(tasks, tasks_history) {
-            eprintln!("{err}");
-        };
+        if tasks.is_empty() {
+            tasks_history.clear();
+        }
+
+        tasks_file_manager::save(tasks, tasks_history);
 
         Ok(())
     }
 
-    pub fn mark_expired_tasks_as_status_expired(tasks: &mut Vec<Task>) {
-        for task in tasks.into_iter() {
+    /// Moves deleted tasks together with their history to the trash, shared by the command line and tui
+    pub(crate) fn trash_deleted(
+        deleted: Vec<Task>,
+        tasks_history: &mut HashMap<String, Vec<HistoryEntry>>,
+    ) -> Result<(), String> {
+        let deleted = deleted
+            .into_iter()
+            .map(|task| {
+                let history = tasks_history.remove(&task.label).unwrap_or_default();
+                (task, history)
+            })
+            .collect();
+
+        trash::put(deleted).map_err(|err| err.to_string())
+    }
+
+    /// Number part of the label
+    pub fn id(&self) -> i32 {
+        self.label
+            .split_whitespace()
+            .last()
+            .and_then(|id| id.parse().ok())
+            .unwrap_or_default()
+    }
+
+    /// Thing is stored surrounded with quotes
+    fn unquoted_thing(&self) -> &str {
+        self.thing
+            .strip_prefix('"')
+            .and_then(|s| s.strip_suffix('"'))
+            .unwrap_or(&self.thing)
+    }
+
+    /// Words of the thing starting with #, without the #
+    pub fn tags(&self) -> Vec<String> {
+        self.unquoted_thing()
+            .split_whitespace()
+            .filter_map(|word| word.strip_prefix('#'))
+            .map(|tag| {
+                tag.trim_end_matches([',', '.', ';', ':', '!', '?'])
+                    .to_lowercase()
+            })
+            .filter(|tag| !tag.is_empty())
+            .collect()
+    }
+
+    fn deadline_date(&self) -> DateTime<Local> {
+        self.deadline.date
+    }
+
+    fn created_date(&self) -> Option<DateTime<Local>> {
+        self.created
+    }
+
+    fn modified_date(&self) -> Option<DateTime<Local>> {
+        self.modified
+    }
+
+    /// Date of the status if it carries one, otherwise the deadline
+    fn effective_date(&self) -> DateTime<Local> {
+        match self.status {
+            TaskStatus::Completed(date)
+            | TaskStatus::Postponed(date)
+            | TaskStatus::Expired(date)
+            | TaskStatus::Aborted(date) => date,
+            TaskStatus::Todo => self.deadline.date,
+        }
+    }
+
+    /// Task is still actionable but its date already passed
+    fn is_overdue(&self, now: &DateTime<Local>) -> bool {
+        match self.status {
+            TaskStatus::Todo | TaskStatus::Postponed(_) | TaskStatus::Expired(_) => {
+                self.effective_date() < *now
+            }
+            _ => false,
+        }
+    }
+
+    /// Expirations are recorded in the history, so they show up in the log
+    pub fn mark_expired_tasks_as_status_expired(
+        tasks: &mut [Task],
+        tasks_history: &mut HashMap<String, Vec<HistoryEntry>>,
+    ) {
+        let mut expired = false;
+
+        for task in tasks.iter_mut() {
             if !cli_manager::match_status(task, "expired") {
+                let prev_task = task.clone();
+
                 match task.status {
                     TaskStatus::Postponed(date) => {
                         let now = DateTime::date_now();
@@ -222,18 +636,31 @@ impl Task {
                             task.status = TaskStatus::Expired(date);
                         }
                     }
-                    _ => {
+                    TaskStatus::Todo => {
                         let now = DateTime::date_now();
                         if task.deadline.date < now {
                             task.status = TaskStatus::Expired(task.deadline.date);
                         }
                     }
+                    // completed and aborted tasks keep the date of their status
+                    _ => (),
+                }
+
+                if *task != prev_task {
+                    history::record(tasks_history, ChangeKind::Expire, prev_task, task);
+                    expired = true;
                 }
             }
         }
         if let Err(err) = tasks_file_manager::save_tasks(tasks) {
             eprint!("{err}");
         };
+
+        if expired {
+            if let Err(err) = tasks_file_manager::save_tasks_history(tasks, tasks_history) {
+                eprint!("{err}");
+            };
+        }
     }
 
     pub fn find_available_ids(ids: &mut Vec<i32>) -> Vec<i32> {
@@ -268,7 +695,7 @@ impl Task {
         available_ids
     }
 
-    pub fn get_all_ids(tasks: &Vec<Task>) -> Vec<i32> {
+    pub fn get_all_ids(tasks: &[Task]) -> Vec<i32> {
         tasks
             .iter()
             .filter_map(|task| {
@@ -302,15 +729,15 @@ impl fmt::Display for Task {
     }
 }
 
-#[derive(Debug, Clone, Copy)]
+#[derive(Debug, Clone, Copy, PartialEq)]
 struct Deadline {
     // isPostponed: bool,
     date: DateTime<Local>,
 }
 
-#[derive(Debug, Clone, Copy)]
+#[derive(Debug, Clone, Copy, PartialEq)]
 pub enum TaskStatus {
-    Completed,
+    Completed(DateTime<Local>),
     Todo,
     Postponed(DateTime<Local>),
     Expired(DateTime<Local>),
@@ -320,88 +747,488 @@ pub enum TaskStatus {
 impl fmt::Display for TaskStatus {
     fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
         match *self {
+            TaskStatus::Completed(date) => {
+                write!(f, "Completed({})", DateTime::date_user_formating(date))
+            }
             TaskStatus::Postponed(date) => {
                 write!(f, "Postponed({})", DateTime::date_user_formating(date))
             }
             TaskStatus::Expired(date) => {
                 write!(f, "Expired({})", DateTime::date_user_formating(date))
             }
-            TaskStatus::Aborted(date) => {
-                write!(f, "Aborted({})", DateTime::date_user_formating(date))
+            TaskStatus::Aborted(date) => {
+                write!(f, "Aborted({})", DateTime::date_user_formating(date))
+            }
+            ref other => write!(f, "{:?}", other),
+        }
+    }
+}
+
+impl Deadline {
+    fn new(input: &String) -> Result<Self, String> {
+        match DateTime::parse_formated_string_to_datetime(input, DateTime::date_now()) {
+            Ok(date) => Ok(Self { date }),
+            Err(err) => Err(format!("Error occurred while parsing the date. Make sure it's in the right format:\n{}\nMessage: {}", 
+            "Deadline: format: 10/06/2023 12:30 | 10/06/2023 | tomorrow 12:30 | today 12:30 | next 12:30 | tomorrow | next | today | 12:30", err)),
+        }
+    }
+}
+
+// Or the switches in the near future and maybe
+fn handle_action_by_argument(
+    tasks: &mut Vec<Task>,
+    tasks_history: &mut HashMap<String, Vec<HistoryEntry>>,
+    switches_with_args: String,
+) -> Result<(), String> {
+    // some switch is always present
+    let switches = cli_manager::parse_to_switches_and_arguments(&switches_with_args)?.unwrap();
+
+    let switches_without_running_command = if switches[1..].is_empty() {
+        None
+    } else {
+        Some(switches[1..].to_vec())
+    };
+
+    for (switch, args) in switches.iter() {
+        match switch.trim_start_matches("--") {
+            "1" | "show-tasks" => {
+                return cli_manager::show_tasks(tasks, switches_without_running_command)
+            }
+            "parse" => match args {
+                Some(args) => {
+                    let path = args.join("");
+                    utils::parse_redirected_stream_of_show_tasks(
+                        tasks,
+                        tasks_history,
+                        PathBuf::from(path),
+                    )
+                        .map_err(|err| err.to_string())
+                }
+                None => Err(format!("switch {switch} requiers additional arguments")),
+            },
+            _ => Err("switch does not exists".to_string()),
+        }?;
+    }
+
+    Ok(())
+}
+
+/// Selects tasks by filtering switches of show tasks, previews them and applies the command after confirmation
+fn handle_bulk_command(
+    tasks: &mut Vec<Task>,
+    tasks_history: &mut HashMap<String, Vec<HistoryEntry>>,
+    command: &CommandSpec,
+    switches: Switches,
+) -> Result<(), String> {
+    let mut filters: Switches = vec![];
+    let mut assume_yes = false;
+    let mut thing = None;
+    let mut status = None;
+    let mut deadline = None;
+
+    for (switch, args) in switches {
+        match switch.to_lowercase().trim_start_matches("--") {
+            "yes" => assume_yes = true,
+            "set-thing" => thing = args.map(|args| args.join(" ")),
+            "set-status" => status = args.map(|args| args.join(" ")),
+            "set-deadline" => deadline = args.map(|args| args.join(" ")),
+            _ => filters.push((switch, args)),
+        }
+    }
+
+    if filters.is_empty() {
+        return Err(format!(
+            "Command {} requires a task id or filtering switches",
+            command.name
+        ));
+    }
+
+    if command.name == "edit" && thing.is_none() && status.is_none() && deadline.is_none() {
+        return Err("Command edit requires --set-thing, --set-status or --set-deadline when tasks are selected by filtering switches".to_string());
+    }
+
+    let matched = cli_manager::filter_tasks(tasks, Some(filters), OutputFormat::Plain)?;
+
+    if matched.is_empty() {
+        println!("No tasks matched the filters");
+        return Ok(());
+    }
+
+    print!(
+        "{}",
+        OutputFormat::Table.render_tasks_for_terminal(&matched)
+    );
+
+    let action = match command.name {
+        "done" => "marked as completed",
+        "edit" => "edited",
+        _ => "deleted",
+    };
+    println!("{} tasks will be {action}", matched.len());
+
+    if !assume_yes && !cli_manager::confirm("Proceed?") {
+        println!("Nothing was changed");
+        return Ok(());
+    }
+
+    let labels = matched
+        .into_iter()
+        .map(|task| task.label)
+        .collect::<Vec<_>>();
+
+    if command.name == "delete" {
+        let (deleted, kept) = tasks
+            .drain(..)
+            .partition(|task| labels.contains(&task.label));
+        *tasks = kept;
+
+        if let Err(err) = Task::trash_deleted(deleted, tasks_history) {
+            eprintln!("{err}");
+        }
+
+        println!("Successfully deleted {} tasks", labels.len());
+    } else {
+        let status = if command.name == "done" {
+            Some("completed".to_string())
+        } else {
+            status
+        };
+
+        let mut updated = 0;
+        for task in tasks.iter_mut().filter(|task| labels.contains(&task.label)) {
+            match task.apply_update(
+                tasks_history,
+                thing.as_deref(),
+                status.as_deref(),
+                deadline.as_deref(),
+            ) {
+                Ok(()) => updated += 1,
+                Err(err) => eprintln!("{}: {err}", task.label),
+            }
+        }
+
+        println!("Successfully updated {updated} of {} tasks", labels.len());
+    }
+
+    tasks_file_manager::save(tasks, tasks_history);
+
+    Ok(())
+}
+
+/// Commands that run without the interactive prompt, e.g. rutodo add "thing" --deadline tomorrow
+fn handle_command_by_argument(
+    tasks: &mut Vec<Task>,
+    tasks_history: &mut HashMap<String, Vec<HistoryEntry>>,
+    args: &[String],
+) -> Result<(), String> {
+    let (command, consumed) = commands::find_command(args).ok_or(format!(
+        "Unrecognized command {}, see rutodo help",
+        args.first().map(String::as_str).unwrap_or_default()
+    ))?;
+
+    // words to complete can contain anything, including switches
+    if command.name == "__complete" {
+        for candidate in commands::complete(&args[consumed..], tasks) {
+            println!("{candidate}");
+        }
+        return Ok(());
+    }
+
+    let (arguments, switches) = cli_manager::split_command_arguments(&args[consumed..]);
+    let switches = if switches.is_empty() {
+        None
+    } else {
+        Some(switches)
+    };
+
+    if CommandSpec::is_help_requested(&switches) {
+        print!("{}", command.help());
+        return Ok(());
+    }
+
+    command.validate(&switches)?;
+
+    let switch_value = |name: &str| -> Option<String> {
+        switches.iter().flatten().find_map(|(switch, args)| {
+            (switch.trim_start_matches("--") == name).then(|| args.as_ref().map(|x| x.join(" ")))?
+        })
+    };
+
+    let has_switch = |name: &str| {
+        switches
+            .iter()
+            .flatten()
+            .any(|(switch, _)| switch.trim_start_matches("--") == name)
+    };
+
+    let task_number = || -> Result<&str, String> {
+        match arguments.as_slice() {
+            [number] if number.chars().all(|c| c.is_numeric()) => Ok(number),
+            _ => Err(format!(
+                "Command {} requires a single task id",
+                command.name
+            )),
+        }
+    };
+
+    if command.accepts_filters {
+        if arguments.is_empty() {
+            return handle_bulk_command(
+                tasks,
+                tasks_history,
+                command,
+                switches.unwrap_or_default(),
+            );
+        }
+
+        // filtering switches select the tasks only when id is not supplied
+        if let Some((switch, _)) = switches.iter().flatten().find(|(switch, _)| {
+            !command
+                .switches
+                .iter()
+                .any(|x| x.name == switch.trim_start_matches("--"))
+        }) {
+            return Err(format!(
+                "switch {switch} cannot be used together with the task id"
+            ));
+        }
+    }
+
+    match command.name {
+        "add" if arguments.is_empty() && switches.is_none() => {
+            Task::add_task(tasks, tasks_history);
+            Ok(())
+        }
+        "add" => {
+            let thing = arguments.join(" ");
+            if thing.trim().is_empty() {
+                return Err("Task thing cannot be empty".to_string());
+            }
+
+            let deadline = switch_value("deadline")
+                .ok_or("Command add requires --deadline <date>".to_string())?;
+
+            let estimate = switch_value("estimate")
+                .map(|estimate| estimates::parse_estimate(&estimate))
+                .transpose()?
+                .flatten();
+
+            Task::insert_task(
+                tasks,
+                tasks_history,
+                &thing,
+                Deadline::new(&deadline)?,
+                estimate,
+            );
+
+            Ok(())
+        }
+        "done" => Task::update_task(
+            tasks,
+            tasks_history,
+            task_number()?,
+            None,
+            Some("completed"),
+            None,
+        ),
+        "edit" => match switches.as_deref() {
+            None => Err("Command edit requires --thing, --status or --deadline switch".to_string()),
+            Some(switches)
+                if switches
+                    .iter()
+                    .any(|(switch, _)| switch.starts_with("--set-")) =>
+            {
+                Task::update_task(
+                    tasks,
+                    tasks_history,
+                    task_number()?,
+                    switch_value("set-thing").as_deref(),
+                    switch_value("set-status").as_deref(),
+                    switch_value("set-deadline").as_deref(),
+                )
+            }
+            Some([(switch, None)]) if switch == "--editor" => {
+                editor::edit_task(tasks, tasks_history, task_number()?)
+            }
+            Some(_) if has_switch("editor") => {
+                Err("Switch --editor cannot be combined with other switches".to_string())
+            }
+            Some([(switch, Some(note))]) if switch == "--note" => {
+                notes::set_note(tasks, tasks_history, task_number()?, &note.join(" "))
+            }
+            Some(_) if has_switch("note") => {
+                Err("Switch --note cannot be combined with other switches".to_string())
+            }
+            Some([(switch, Some(estimate))]) if switch == "--estimate" => {
+                estimates::set_estimate(tasks, tasks_history, task_number()?, &estimate.join(""))
+            }
+            Some(_) if has_switch("estimate") => {
+                Err("Switch --estimate requires a duration and cannot be combined with other switches".to_string())
+            }
+            // field without value is asked for
+            Some([(switch, None)]) => Task::edit_task(
+                tasks,
+                task_number()?.to_string(),
+                &Some(switch.clone()),
+                tasks_history,
+            ),
+            Some(_) => Task::update_task(
+                tasks,
+                tasks_history,
+                task_number()?,
+                switch_value("thing").as_deref(),
+                switch_value("status").as_deref(),
+                switch_value("deadline").as_deref(),
+            ),
+        },
+        "revert" => match arguments.as_slice() {
+            [number, version] if number.chars().all(|c| c.is_numeric()) => {
+                let fields = ["thing", "status", "deadline"]
+                    .into_iter()
+                    .filter(|field| has_switch(field))
+                    .collect::<Vec<_>>();
+
+                history::revert(tasks, tasks_history, number, version, &fields)
+            }
+            _ => Err("Command revert requires a task id and a version".to_string()),
+        },
+        "annotate" => match arguments.split_first() {
+            Some((number, text)) if number.chars().all(|c| c.is_numeric()) => {
+                notes::annotate(tasks, tasks_history, number, &text.join(" "))
+            }
+            _ => Err("Command annotate requires a task id and a text".to_string()),
+        },
+        "attach" => match arguments.split_first() {
+            Some((number, reference))
+                if number.chars().all(|c| c.is_numeric()) && !reference.is_empty() =>
+            {
+                attachments::attach(tasks, tasks_history, number, &reference.join(" "))
+            }
+            _ => Err("Command attach requires a task id and a file or URL".to_string()),
+        },
+        "open" => match arguments.as_slice() {
+            [number] if number.chars().all(|c| c.is_numeric()) => {
+                attachments::open(tasks, number, None)
+            }
+            [number, attachment] if number.chars().all(|c| c.is_numeric()) => {
+                attachments::open(tasks, number, Some(attachment))
+            }
+            _ => Err("Command open requires a task id".to_string()),
+        },
+        "check" if arguments.is_empty() => attachments::check(tasks),
+        "start" => timer::start(tasks, tasks_history, task_number()?),
+        "stop" if arguments.is_empty() => timer::stop(tasks, tasks_history),
+        "log-time" => match arguments.split_first() {
+            Some((number, duration))
+                if number.chars().all(|c| c.is_numeric()) && !duration.is_empty() =>
+            {
+                timer::log_time(tasks, tasks_history, number, &duration.join(""))
+            }
+            _ => Err("Command log-time requires a task id and a duration".to_string()),
+        },
+        "reopen" => Task::reopen_task(
+            tasks,
+            tasks_history,
+            task_number()?,
+            switch_value("deadline").as_deref(),
+            &switch_value("reason").ok_or("Command reopen requires --reason switch")?,
+        ),
+        "delete" => match arguments.as_slice() {
+            ["all"] => {
+                Task::delete_task(tasks, tasks_history, "all".to_string(), has_switch("yes"))
             }
-            ref other => write!(f, "{:?}", other),
+            _ => Task::delete_task(tasks, tasks_history, task_number()?.to_string(), false),
+        },
+        "show tasks" => cli_manager::show_tasks(tasks, switches),
+        "report estimates" if arguments.is_empty() => estimates::report(tasks, switches),
+        "show task" => {
+            cli_manager::show_task_with_switches(tasks, tasks_history, task_number()?, switches)
         }
-    }
-}
+        "log" => history::log(
+            tasks_history,
+            switch_value("since").as_deref(),
+            switch_value("user").as_deref(),
+            switch_value("op").as_deref(),
+        ),
+        "undo" => journal::undo(tasks, tasks_history, journal::parse_count(&arguments)?),
+        "redo" => journal::redo(tasks, tasks_history, journal::parse_count(&arguments)?),
+        "trash list" if arguments.is_empty() => trash::list(),
+        "trash restore" => trash::restore(tasks, tasks_history, task_number()?),
+        "trash empty" => {
+            if !has_switch("yes") && !cli_manager::confirm("Permanently remove every deleted task?")
+            {
+                println!("Nothing was changed");
+                return Ok(());
+            }
 
-impl Deadline {
-    fn new(input: &String) -> Result<Self, String> {
-        match DateTime::parse_formated_string_to_datetime(input, DateTime::date_now()) {
-            Ok(date) => Ok(Self { date }),
-            Err(err) => Err(format!("Error occurred while parsing the date. Make sure it's in the right format:\n{}\nMessage: {}", 
-            "Deadline: format: 10/06/2023 12:30 | 10/06/2023 | tomorrow 12:30 | today 12:30 | next 12:30 | tomorrow | next | today | 12:30", err)),
+            trash::empty()
+        }
+        "tui" => tui::run(tasks, tasks_history),
+        "completions" => match arguments.as_slice() {
+            [shell] => {
+                print!("{}", commands::completion_script(shell)?);
+                Ok(())
+            }
+            _ => Err(format!("Usage: {}", command.usage)),
+        },
+        _ => {
+            cli_manager::show_command_line_usage();
+            Ok(())
         }
     }
 }
 
-// Or the switches in the near future and maybe
-fn handle_action_by_argument(
-    tasks: &mut Vec<Task>,
-    switches_with_args: String,
-) -> Result<(), String> {
-    // some switch is always present
-    let switches = cli_manager::parse_to_switches_and_arguments(&switches_with_args)?.unwrap();
-
-    let switches_without_running_command = if switches[1..].is_empty() {
-        None
-    } else {
-        Some(switches[1..].to_vec())
-    };
+/// Words of the action typed in the prompt, only the words of the command are lowercased, so texts and paths keep their case
+fn command_words(action: &str) -> Vec<String> {
+    let mut words = action
+        .split_whitespace()
+        .map(String::from)
+        .collect::<Vec<_>>();
 
-    println!("{switches:?}");
+    let consumed = commands::find_command(&words).map_or(0, |(_, consumed)| consumed);
 
-    for (switch, args) in switches.iter() {
-        match switch.trim_start_matches("--") {
-            "1" | "show-tasks" => {
-                return cli_manager::show_tasks(tasks, switches_without_running_command)
-            }
-            "parse" => match args {
-                Some(args) => {
-                    let path = args.join("");
-                    utils::parse_redirected_stream_of_show_tasks(tasks, PathBuf::from(path))
-                        .map_err(|err| err.to_string())
-                }
-                None => Err(format!("switch {switch} requiers additional arguments")),
-            },
-            _ => Err("switch does not exists".to_string()),
-        }?;
+    for word in words.iter_mut().take(consumed) {
+        *word = word.to_lowercase();
     }
 
-    Ok(())
+    words
 }
 
 pub fn spawn_cli_interface(
     tasks: &mut Vec<Task>,
-    mut tasks_history: &mut HashMap<String, Vec<Task>>,
+    tasks_history: &mut HashMap<String, Vec<HistoryEntry>>,
 ) -> Result<(), String> {
     let args = std::env::args().collect::<Vec<String>>();
 
     if args.len() > 1 {
-        let switch = &args[1..].join(" ").to_string();
-        return handle_action_by_argument(tasks, switch.to_owned());
+        let before = (tasks.clone(), tasks_history.clone());
+
+        // switches are kept for backward compatibility, e.g. rutodo --show-tasks
+        let result = if args[1].starts_with("--") {
+            let switch = &args[1..].join(" ").to_string();
+            handle_action_by_argument(tasks, tasks_history, switch.to_owned())
+        } else {
+            handle_command_by_argument(tasks, tasks_history, &args[1..])
+        };
+
+        if !journal::is_journal_command(&[args[1].as_str()]) {
+            if let Err(err) = journal::record(&args[1..].join(" "), &before, tasks, tasks_history) {
+                eprintln!("{err}");
+            }
+        }
+
+        return result;
     }
 
     cli_manager::show_user_actions();
 
-    loop {
-        let mut action = String::new();
-
-        print!("prompt: ");
-        io::stdout().flush().expect("Failed to flush stdout");
+    let mut prompt = prompt::Prompt::new();
 
-        io::stdin()
-            .read_line(&mut action)
-            .expect("Unable to write to the writable buffer");
+    loop {
+        let action = match prompt.read_line(&timer::prompt(tasks), tasks) {
+            Some(action) => action,
+            None => return Ok(()),
+        };
 
         let switches = match cli_manager::parse_to_switches_and_arguments(action.trim()) {
             Ok(switches) => switches,
@@ -411,7 +1238,37 @@ pub fn spawn_cli_interface(
             }
         };
 
+        if CommandSpec::is_help_requested(&switches) {
+            let words = action
+                .split_whitespace()
+                .take_while(|word| !word.starts_with("--"))
+                .collect::<Vec<_>>();
+
+            if let Some((command, _)) = commands::find_command(&words) {
+                print!("{}", command.help());
+                continue;
+            }
+        }
+
+        let before = (tasks.clone(), tasks_history.clone());
+        let command_words = command_words(&action);
+
         match action.trim().to_lowercase().to_string().as_str() {
+            action
+                if journal::is_journal_command(&action.split_whitespace().collect::<Vec<_>>()) =>
+            {
+                let words = action.split_whitespace().collect::<Vec<_>>();
+
+                let result = journal::parse_count(&words[1..]).and_then(|count| match words[0] {
+                    "undo" => journal::undo(tasks, tasks_history, count),
+                    _ => journal::redo(tasks, tasks_history, count),
+                });
+
+                if let Err(err) = result {
+                    eprintln!("{err}");
+                }
+                continue;
+            }
             action
                 if action == "1"
                     || action == "show tasks"
@@ -435,50 +1292,76 @@ pub fn spawn_cli_interface(
                     action if action.starts_with("show task ") => {
                         let task_label_number = action.trim_start_matches("show task ");
 
-                        match switches {
-                            Some(switches) => {
-                                let task_label_number = &task_label_number
-                                    [0..task_label_number.find("--").unwrap()]
-                                    .trim();
-
-                                for (switch, _) in switches {
-                                    let switch = switch.trim_start_matches("--");
-                                    match switch {
-                                        "history" => {
-                                            let label = format!("Task {}", task_label_number);
-                                            
-                                            match tasks_history.get(&label) {
-                                                Some(tasks_history) => {
-                                                    println!("Tasks history");
-                                                    for task in tasks_history {
-                                                        println!("{task}");
-                                                    }
-                                                }
-                                                None => {
-                                                    eprintln!("No previous versions of this task")
-                                                }
-                                            }
-                                        }
-                                        "help" => println!(
-                                            r"Description: Print task by id
-Usage: show task <Task lable ID> [switch]
-Output: UTF-8 encoded string
-    --history Display previous versions of task
-    
-    --help Display this message
-"
-                                        ),
-                                        _ => eprintln!("Inexistent switch"),
-                                    };
-                                }
-                            }
-                            None => cli_manager::show_task(tasks, task_label_number),
+                        let task_label_number = match task_label_number.find("--") {
+                            Some(idx) => task_label_number[..idx].trim(),
+                            None => task_label_number.trim(),
+                        };
+
+                        if let Err(err) = cli_manager::show_task_with_switches(
+                            tasks,
+                            tasks_history,
+                            task_label_number,
+                            switches,
+                        ) {
+                            eprintln!("{err}");
                         }
                     }
                     _ => (),
                 };
             }
-            "2" | "add task" | "add" => Task::add_task(tasks),
+            "2" | "add task" | "add" => Task::add_task(tasks, tasks_history),
+            action
+                if [
+                    "done ",
+                    "3 ",
+                    "edit task ",
+                    "edit ",
+                    "4 ",
+                    "delete task ",
+                    "delete ",
+                ]
+                .iter()
+                .any(|prefix| {
+                    action
+                        .strip_prefix(prefix)
+                        .is_some_and(|rest| rest.trim_start().starts_with("--"))
+                }) =>
+            {
+                let words = action
+                    .split_whitespace()
+                    .take_while(|word| !word.starts_with("--"))
+                    .collect::<Vec<_>>();
+
+                let result = match commands::find_command(&words) {
+                    Some((command, _)) => command.validate(&switches).and_then(|()| {
+                        handle_bulk_command(
+                            tasks,
+                            tasks_history,
+                            command,
+                            switches.unwrap_or_default(),
+                        )
+                    }),
+                    None => Err("Unrecognized program action".to_string()),
+                };
+
+                if let Err(err) = result {
+                    eprintln!("{err}");
+                }
+            }
+            action if action.starts_with("done ") => {
+                let task_number = action.trim_start_matches("done ").trim();
+
+                if let Err(err) = Task::update_task(
+                    tasks,
+                    tasks_history,
+                    task_number,
+                    None,
+                    Some("completed"),
+                    None,
+                ) {
+                    eprintln!("{err}");
+                }
+            }
             action
                 if action.starts_with("3 ")
                     || action.starts_with("edit task ")
@@ -491,24 +1374,22 @@ Output: UTF-8 encoded string
                     .trim()
                     .to_string();
 
-                let splited = user_input.split(" ").collect::<Vec<&str>>();
+                let task_number = user_input.split_whitespace().next().unwrap_or_default();
+
+                if task_number.is_empty() || !task_number.chars().all(|c| c.is_numeric()) {
+                    eprintln!("Invalid task number");
+                    continue;
+                }
 
-                let (task_number, switch_field) = if splited.len() == 1 {
-                    (user_input, None)
-                } else if splited.len() == 2 && splited[1].starts_with("--") {
-                    (splited[0].to_string(), Some(splited[1].to_string()))
+                // edit with switches is run the same way as from the command line, only the field is asked for without them
+                let result = if user_input == task_number {
+                    Task::edit_task(tasks, task_number.to_string(), &None, tasks_history)
                 } else {
-                    (String::new(), None)
+                    handle_command_by_argument(tasks, tasks_history, &command_words)
                 };
 
-                if task_number.chars().all(|c| c.is_numeric()) {
-                    if let Err(err) =
-                        Task::edit_task(tasks, task_number, &switch_field, &mut tasks_history)
-                    {
-                        eprintln!("{err}");
-                    }
-                } else {
-                    eprintln!("Invalid task number")
+                if let Err(err) = result {
+                    eprintln!("{err}");
                 }
             }
             action
@@ -516,31 +1397,71 @@ Output: UTF-8 encoded string
                     | action.starts_with("delete task ")
                     | action.starts_with("delete ") =>
             {
-                // task_number =| numeric_string | "all"
-                let mut task_number = action
+                // task_number =| numeric_string | "all" [--yes]
+                let task_number = action
                     .trim_start_matches("4 ")
                     .trim_start_matches("delete task ")
                     .trim_start_matches("delete ")
-                    .trim()
-                    .to_string();
-                task_number =
-                    match task_number.chars().all(|c| c.is_numeric()) || task_number == "all" {
-                        true => task_number,
-                        false => {
+                    .trim();
+
+                let (task_number, assume_yes) =
+                    match task_number.split_whitespace().collect::<Vec<_>>()[..] {
+                        ["all", "--yes"] => ("all".to_string(), true),
+                        [number] if number.chars().all(|c| c.is_numeric()) || number == "all" => {
+                            (number.to_string(), false)
+                        }
+                        _ => {
                             eprintln!("Invalid task number");
                             continue;
                         }
                     };
 
-                if let Err(err) = Task::delete_task(tasks, tasks_history, task_number) {
+                if let Err(err) = Task::delete_task(tasks, tasks_history, task_number, assume_yes) {
+                    eprintln!("{err}");
+                }
+            }
+            action
+                if action == "trash"
+                    || action.starts_with("trash ")
+                    || action == "show deleted"
+                    || action.starts_with("restore ")
+                    || action.starts_with("reopen ")
+                    || action.starts_with("revert ")
+                    || action.starts_with("annotate ")
+                    || action.starts_with("attach ")
+                    || action.starts_with("open ")
+                    || action == "check"
+                    || action.starts_with("start ")
+                    || action == "stop"
+                    || action.starts_with("log-time ")
+                    || action == "report estimates"
+                    || action.starts_with("report estimates ")
+                    || action == "log"
+                    || action.starts_with("log ") =>
+            {
+                if let Err(err) = handle_command_by_argument(tasks, tasks_history, &command_words) {
                     eprintln!("{err}");
                 }
             }
             "exit" => std::process::exit(0),
             "help" => cli_manager::show_user_actions(),
             "cls" => cli_manager::clear_console(),
-            _ => eprintln!("Unrecognized program action"),
+            // rest of the commands, e.g. tui or completions, are run the same way as from the command line
+            _ => {
+                if commands::find_command(&command_words).is_none() {
+                    eprintln!("Unrecognized program action");
+                    continue;
+                }
+
+                if let Err(err) = handle_command_by_argument(tasks, tasks_history, &command_words) {
+                    eprintln!("{err}");
+                }
+            }
         };
+
+        if let Err(err) = journal::record(&action, &before, tasks, tasks_history) {
+            eprintln!("{err}");
+        }
     }
 }
 
@@ -582,8 +1503,6 @@ help => spawn this message"#
 
         help_message();
 
-        use TaskStatus::{Aborted, Completed, Postponed, Todo};
-
         loop {
             let new_value = cli_manager::get_labeled_input_from_user("status")
                 .to_lowercase()
@@ -597,32 +1516,10 @@ help => spawn this message"#
                 break;
             }
 
-            let new_status = match new_value.as_str() {
-                "completed" => Completed,
-                "todo" => Todo,
-                new_value if new_value.starts_with("postponed ") => {
-                    let date_part = &new_value.split(" ").collect::<Vec<_>>()[1..];
-
-                    let date_from = match task.status {
-                        Postponed(date) => date,
-                        _ => task.deadline.date,
-                    };
-
-                    let new_date = DateTime::parse_formated_string_to_datetime(
-                        &date_part.join(" "),
-                        date_from,
-                    );
-
-                    match new_date {
-                        Ok(date) => Postponed(date),
-                        Err(_) => {
-                            continue;
-                        }
-                    }
-                }
-                "aborted" => Aborted(DateTime::date_now()),
-                _ => {
-                    eprintln!("No such status available");
+            let new_status = match task.parse_status(&new_value) {
+                Ok(status) => status,
+                Err(err) => {
+                    eprintln!("{err}");
                     continue;
                 }
             };
@@ -635,6 +1532,36 @@ help => spawn this message"#
             break;
         }
     }
+
+    fn edit_deadline(task: &mut Task) {
+        cli_manager::clear_console();
+
+        println!(
+            "{task}\nDeadline format: 10/06/2023 12:30 | 10/06/2023 | tomorrow 12:30 | today 12:30 | next 12:30 | tomorrow | next | today | 12:30"
+        );
+
+        loop {
+            let new_value = cli_manager::get_labeled_input_from_user("deadline");
+
+            if new_value == "exit" {
+                cli_manager::clear_console_and_display_help();
+                break;
+            }
+
+            if let Err(err) =
+                Deadline::new(&new_value).and_then(|deadline| task.set_deadline(deadline))
+            {
+                eprintln!("{err}");
+                continue;
+            }
+
+            cli_manager::clear_console();
+
+            println!("Updated task:\n{task}");
+
+            break;
+        }
+    }
 }
 
 pub trait DateTimeFormatter {
@@ -769,6 +1696,12 @@ impl DateTimeFormatter for DateTime<Local> {
 pub mod cli_manager {
     use super::*;
 
+    /// Switches in order of appearance with their arguments, e.g. ("--date", Some(["-gt", "today"]))
+    pub type Switches<'a> = Vec<(String, Option<Vec<&'a str>>)>;
+
+    /// Start inclusive and end exclusive, None is unbounded
+    pub(crate) type DateWindow = (Option<DateTime<Local>>, Option<DateTime<Local>>);
+
     pub fn get_labeled_input_from_user(field_name: &str) -> String {
         let mut input = String::new();
 
@@ -786,9 +1719,32 @@ pub mod cli_manager {
         return input.trim().to_string();
     }
 
-    pub fn parse_to_switches_and_arguments<'a>(
-        action: &'a str,
-    ) -> Result<Option<Vec<(String, Option<Vec<&'a str>>)>>, String> {
+    /// Splits already tokenized command line into positional arguments and switches,
+    /// so quoted arguments like "buy milk" stay intact
+    pub fn split_command_arguments(args: &[String]) -> (Vec<&str>, Switches<'_>) {
+        let mut arguments = vec![];
+        let mut switches: Switches = vec![];
+
+        for arg in args {
+            if arg.starts_with("--") {
+                switches.push((arg.to_lowercase(), None));
+            } else if let Some((_, switch_args)) = switches.last_mut() {
+                switch_args.get_or_insert_with(Vec::new).push(arg.as_str());
+            } else {
+                arguments.push(arg.as_str());
+            }
+        }
+
+        (arguments, switches)
+    }
+
+    /// Asks the user, anything but y | yes is treated as no
+    pub fn confirm(question: &str) -> bool {
+        let answer = get_labeled_input_from_user(&format!("{question} [y/N]"));
+        matches!(answer.to_lowercase().as_str(), "y" | "yes")
+    }
+
+    pub fn parse_to_switches_and_arguments(action: &str) -> Result<Option<Switches<'_>>, String> {
         let switches_with_arguments = match action.find("--") {
             Some(idx) => &action[idx..],
             None => return Ok(None),
@@ -798,7 +1754,7 @@ pub mod cli_manager {
             return Err(format!("Invalid switch format"));
         }
 
-        let mut switches: Vec<(String, Option<Vec<&str>>)> = Vec::new();
+        let mut switches: Switches = Vec::new();
 
         let switches_with_arguments = switches_with_arguments
             .split_whitespace()
@@ -848,64 +1804,328 @@ pub mod cli_manager {
         Ok(Some(switches))
     }
 
-    pub fn show_task(tasks: &Vec<Task>, task_label_number: &str) {
-        if task_label_number.chars().all(|c| c.is_numeric()) {
-            match tasks
-                .iter()
-                .find(|task| task.label.split_whitespace().last().unwrap() == task_label_number)
-            {
-                Some(task) => println!("{task}"),
-                None => eprintln!("Could not found task with this label number"),
+    pub fn show_task_with_switches(
+        tasks: &[Task],
+        tasks_history: &HashMap<String, Vec<HistoryEntry>>,
+        task_label_number: &str,
+        switches: Option<Switches>,
+    ) -> Result<(), String> {
+        if CommandSpec::is_help_requested(&switches) {
+            print!("{}", commands::SHOW_TASK.help());
+            return Ok(());
+        }
+
+        commands::SHOW_TASK.validate(&switches)?;
+
+        let switches = match switches {
+            Some(switches) => switches,
+            None => return show_task(tasks, task_label_number, OutputFormat::Plain),
+        };
+
+        let format = match switches.iter().find(|(switch, _)| switch == "--format") {
+            Some((_, Some(args))) => OutputFormat::parse(&args.join(""))?,
+            Some((_, None)) => {
+                return Err("switch --format requiers additional arguments".to_string())
+            }
+            None => OutputFormat::Plain,
+        };
+
+        let has_history = switches
+            .iter()
+            .any(|(switch, _)| switch.trim_start_matches("--") == "history");
+
+        if !has_history {
+            return show_task(tasks, task_label_number, format);
+        }
+
+        let label = format!("Task {}", task_label_number);
+
+        match tasks_history.get(&label) {
+            // machine readable formats keep listing the previous versions
+            Some(entries) if format.is_machine_readable() => {
+                print!("{}", format.render_tasks(&history::versions(entries)));
+            }
+            Some(entries) => {
+                println!("Tasks history");
+                // numbers are the versions taken by revert
+                for (idx, entry) in entries.iter().enumerate() {
+                    println!("{}. {}", idx + 1, entry.changelog_line());
+                }
+            }
+            None => eprintln!("No previous versions of this task"),
+        }
+
+        Ok(())
+    }
+
+    /// Fails if there is no task with given id, so the command exits with 1
+    pub fn show_task(
+        tasks: &[Task],
+        task_label_number: &str,
+        format: OutputFormat,
+    ) -> Result<(), String> {
+        let task = tasks
+            .iter()
+            .find(|task| task.label.split_whitespace().last() == Some(task_label_number))
+            .ok_or("Could not found task with this label number".to_string())?;
+
+        if format == OutputFormat::Plain {
+            let timestamp = |date: Option<DateTime<Local>>| {
+                date.map(DateTime::date_user_formating)
+                    .unwrap_or("unknown".to_string())
+            };
+
+            println!(
+                "{task}Created: {}\nModified: {}\n{}{}{}{}",
+                timestamp(task.created),
+                timestamp(task.modified),
+                estimates::describe(task),
+                timer::describe(task),
+                notes::describe(task),
+                attachments::describe(task)
+            )
+        } else {
+            print!("{}", format.render_task(task))
+        }
+
+        Ok(())
+    }
+
+    /// Comparison built from the arguments of `--date` and `--deadline` switches, a date without hours:minutes
+    /// stands for the whole day and a date with them for the whole minute, so -le today includes the rest of today
+    enum DateFilter {
+        // inclusive
+        Since(DateTime<Local>),
+        // exclusive
+        Before(DateTime<Local>),
+        // from inclusive, to exclusive
+        Range(DateTime<Local>, DateTime<Local>),
+    }
+
+    impl DateFilter {
+        fn parse(args: &[&str]) -> Result<Self, String> {
+            if let [name] = args {
+                if let Some((from, to)) = named_range(name) {
+                    return Ok(DateFilter::Range(from, to));
+                }
+            }
+
+            let method = args.first().copied().unwrap_or_default();
+            let rest = &args[1.min(args.len())..];
+
+            let filter = match method {
+                "-gt" => DateFilter::Since(parse_filter_span(rest)?.1),
+                "-ge" => DateFilter::Since(parse_filter_span(rest)?.0),
+                "-lt" => DateFilter::Before(parse_filter_span(rest)?.0),
+                "-le" => DateFilter::Before(parse_filter_span(rest)?.1),
+                "-between" => {
+                    // dates can span over multiple words, so look for the first split that parses on both sides
+                    let (from, to) = (1..rest.len())
+                        .find_map(|idx| {
+                            let (from, _) = parse_filter_span(&rest[..idx]).ok()?;
+                            let (_, to) = parse_filter_span(&rest[idx..]).ok()?;
+                            Some((from, to))
+                        })
+                        .ok_or("-between requires two dates, e.g. -between today 12/06/2023".to_string())?;
+
+                    if from >= to {
+                        return Err("First date of -between cannot be later than the second one".to_string());
+                    }
+
+                    DateFilter::Range(from, to)
+                }
+                method if method.starts_with('-') => {
+                    return Err(format!("Inexistent filtering method {method}"))
+                }
+                _ => {
+                    let (from, to) = parse_filter_span(args)?;
+                    DateFilter::Range(from, to)
+                }
+            };
+
+            Ok(filter)
+        }
+
+        fn matches(&self, date: &DateTime<Local>) -> bool {
+            match self {
+                DateFilter::Since(from) => date >= from,
+                DateFilter::Before(to) => date < to,
+                DateFilter::Range(from, to) => date >= from && date < to,
+            }
+        }
+
+        fn window(&self) -> DateWindow {
+            match *self {
+                DateFilter::Since(from) => (Some(from), None),
+                DateFilter::Before(to) => (None, Some(to)),
+                DateFilter::Range(from, to) => (Some(from), Some(to)),
             }
         }
     }
 
-    fn filter_by_date_later_than(
-        task_date: &DateTime<Local>,
-        input_date: &DateTime<Local>,
-    ) -> bool {
-        task_date > input_date
+    /// Start and exclusive end of the day, or of the minute if hours:minutes are given
+    fn parse_filter_span(args: &[&str]) -> Result<(DateTime<Local>, DateTime<Local>), String> {
+        let start = parse_filter_date(args)?;
+
+        if has_time_part(args) {
+            Ok((start, start + Duration::minutes(1)))
+        } else {
+            Ok((start, start + Duration::days(1)))
+        }
     }
 
-    fn filter_by_date_earlier_than(
-        task_date: &DateTime<Local>,
-        input_date: &DateTime<Local>,
-    ) -> bool {
-        task_date < input_date
+    /// Window given by the `--date` switch, unbounded on both sides without it
+    pub(crate) fn date_window(switches: &Option<Switches>) -> Result<DateWindow, String> {
+        let date_args = switches.iter().flatten().find_map(|(switch, args)| {
+            (switch.to_lowercase() == "--date").then_some(args.as_deref().unwrap_or_default())
+        });
+
+        match date_args {
+            Some(args) => Ok(DateFilter::parse(args)?.window()),
+            None => Ok((None, None)),
+        }
     }
 
-    fn filter_by_date_equally(task_date: &DateTime<Local>, input_date: &DateTime<Local>) -> bool {
-        task_date == input_date
+    fn parse_filter_date(args: &[&str]) -> Result<DateTime<Local>, String> {
+        if args.is_empty() {
+            return Err("Filtering method requires a date".to_string());
+        }
+
+        DateTime::parse_formated_string_to_datetime(&args.join(" "), DateTime::date_now())
     }
 
-    fn filter_by_date_dmy(task_date: &DateTime<Local>, input_date: &DateTime<Local>) -> bool {
-        task_date.day() == input_date.day()
-            && task_date.month() == input_date.month()
-            && task_date.year() == input_date.year()
+    fn has_time_part(args: &[&str]) -> bool {
+        args.iter().any(|arg| DateTime::is_valid_hm_format(arg))
     }
 
-    pub fn show_tasks(
-        tasks: &Vec<Task>,
-        switches: Option<Vec<(String, Option<Vec<&str>>)>>,
-    ) -> Result<(), String> {
+    /// Ranges that can be used in place of the date: this-week, last-week, next-week, last-7-days, next-7-days
+    pub fn named_range(name: &str) -> Option<(DateTime<Local>, DateTime<Local>)> {
+        let now = DateTime::date_now();
+        let today = DateTime::parse_formated_string_to_datetime(&"today".to_string(), now).ok()?;
+        let monday = today - Duration::days(today.weekday().num_days_from_monday() as i64);
+
+        match name {
+            "this-week" => Some((monday, monday + Duration::weeks(1))),
+            "last-week" => Some((monday - Duration::weeks(1), monday)),
+            "next-week" => Some((monday + Duration::weeks(1), monday + Duration::weeks(2))),
+            "last-7-days" => Some((now - Duration::days(7), now)),
+            "next-7-days" => Some((now, now + Duration::days(7))),
+            _ => None,
+        }
+    }
+
+    /// Tasks without the date, e.g. created before the timestamps were recorded, are filtered out
+    fn filter_by_date(
+        tasks: Vec<Task>,
+        args: &[&str],
+        date_of: fn(&Task) -> Option<DateTime<Local>>,
+    ) -> Result<Vec<Task>, String> {
+        let filter = DateFilter::parse(args)?;
+
+        Ok(tasks
+            .into_iter()
+            .filter(|task| date_of(task).is_some_and(|date| filter.matches(&date)))
+            .collect())
+    }
+
+    /// --sort <deadline | date | created | modified | thing>[:asc | :desc], tasks without the date come first in both orders
+    /// and tasks with equal keys keep their order
+    fn sort_tasks(tasks: &mut [Task], args: &[&str]) -> Result<(), String> {
+        let sort = args.join("").to_lowercase();
+        let (key, order) = sort.split_once(':').unwrap_or((&sort, "asc"));
+
+        let descending = match order {
+            "asc" => false,
+            "desc" => true,
+            order => return Err(format!("Unknown sort order {order}, expected asc or desc")),
+        };
+
+        let by_date = |tasks: &mut [Task], date_of: fn(&Task) -> Option<DateTime<Local>>| {
+            tasks.sort_by(|a, b| match (date_of(a), date_of(b)) {
+                (Some(a), Some(b)) if descending => b.cmp(&a),
+                (a, b) => a.cmp(&b),
+            })
+        };
+
+        match key {
+            "deadline" => by_date(tasks, |task| Some(task.deadline_date())),
+            "date" => by_date(tasks, |task| Some(task.effective_date())),
+            "created" => by_date(tasks, Task::created_date),
+            "modified" => by_date(tasks, Task::modified_date),
+            "thing" => tasks.sort_by(|a, b| {
+                let ordering = a.thing.to_lowercase().cmp(&b.thing.to_lowercase());
+                if descending {
+                    ordering.reverse()
+                } else {
+                    ordering
+                }
+            }),
+            key => {
+                return Err(format!(
+                    "Cannot sort by {key}, available keys: deadline | date | created | modified | thing"
+                ))
+            }
+        }
+
+        Ok(())
+    }
+
+    pub fn show_tasks(tasks: &Vec<Task>, switches: Option<Switches>) -> Result<(), String> {
+        if CommandSpec::is_help_requested(&switches) {
+            print!("{}", commands::SHOW_TASKS.help());
+            return Ok(());
+        }
+
+        commands::SHOW_TASKS.validate(&switches)?;
+
+        let (output_options, switches) = OutputOptions::from_switches(switches)?;
+
+        // --redirect writes plain format if not given, so the file can be read back by --parse
+        let tasks = filter_and_redirect(
+            tasks,
+            switches,
+            output_options.format.unwrap_or(OutputFormat::Plain),
+            Some(&output_options),
+        )?;
+
+        print_tasks(&tasks, &output_options)
+    }
+
+    /// Applies switches one after another
+    pub(crate) fn filter_tasks(
+        tasks: &[Task],
+        switches: Option<Switches>,
+        format: OutputFormat,
+    ) -> Result<Vec<Task>, String> {
+        filter_and_redirect(tasks, switches, format, None)
+    }
+
+    /// --redirect writes the same page of tasks as is displayed by the output switches
+    fn filter_and_redirect(
+        tasks: &[Task],
+        switches: Option<Switches>,
+        format: OutputFormat,
+        output_options: Option<&OutputOptions>,
+    ) -> Result<Vec<Task>, String> {
         match switches {
             Some(switches) => {
                 let switch_args_pair = switches[0].to_owned();
                 let (switch, args) = (switch_args_pair.0, switch_args_pair.1);
                 let switch = switch.to_lowercase();
                 let switch = switch.trim_start_matches("--");
-
-                match switch {
-                    "thing" | "status" => {
-                        if args.is_none() {
-                            return Err(format!("switch {switch} requiers additional arguments"));
-                        }
-                    }
-                    _ => (),
+
+                // --not-<switch> keeps only tasks that the filtering switch would drop
+                let (negated, switch) = match switch.strip_prefix("not-") {
+                    Some(switch) => (true, switch),
+                    None => (false, switch),
                 };
 
+                if negated && !is_filtering_switch(switch, &args) {
+                    return Err(format!("switch --not-{switch} is not available, only filtering switches can be negated"));
+                }
+
                 // We have to clone tasks to operate on own version of vec to make the function reqursive while type valid
-                let mut tasks_clone = tasks.clone();
+                let mut tasks_clone = tasks.to_vec();
 
                 let filtered_by_switch = match switch {
                     "thing" => {
@@ -927,241 +2147,89 @@ pub mod cli_manager {
                         filtered_by_switch
                     }
                     "status" => {
-                        let status = args.unwrap().join("");
+                        let statuses = parse_statuses(&args.unwrap())?;
 
                         let filtered_by_switch = tasks_clone
                             .into_iter()
-                            .filter(|task| match_status(task, &status))
+                            .filter(|task| statuses.iter().any(|status| match_status(task, status)))
                             .collect::<Vec<_>>();
 
                         filtered_by_switch
                     }
-                    "deadline" => {
-                        match args {
-                            Some(args) => {
-                                let method = &args[0..1].join("");
-
-                                let filtering_method: Option<&str> = match method.as_str() {
-                                    "-gt" | "-lt" => Some(method),
-                                    _ => None,
-                                };
-
-                                let parsable_date_format = if filtering_method.is_some() {
-                                    args[1..].join(" ").to_string()
-                                } else {
-                                    args.join(" ").to_string()
-                                };
-
-                                let input_date = DateTime::parse_formated_string_to_datetime(
-                                    &parsable_date_format,
-                                    DateTime::date_now(),
-                                )?;
-
-                                // for performance
-                                let filtered_by_switch = match filtering_method {
-                                    Some(method) => match method {
-                                        "-gt" => tasks_clone
-                                            .into_iter()
-                                            .filter(|task| {
-                                                filter_by_date_later_than(
-                                                    &task.deadline.date,
-                                                    &input_date,
-                                                )
-                                            })
-                                            .collect::<Vec<_>>(),
-                                        "-lt" => tasks_clone
-                                            .into_iter()
-                                            .filter(|task| {
-                                                filter_by_date_earlier_than(
-                                                    &task.deadline.date,
-                                                    &input_date,
-                                                )
-                                            })
-                                            .collect::<Vec<_>>(),
-                                        _ => return Err("Inexistent filtering method".to_string()),
-                                    },
-                                    None => tasks_clone
-                                        .into_iter()
-                                        .filter(|task| {
-                                            let date_parts = parsable_date_format
-                                                .split_whitespace()
-                                                .collect::<Vec<_>>();
-
-                                            if date_parts.len() == 2 {
-                                                filter_by_date_equally(
-                                                    &task.deadline.date,
-                                                    &input_date,
-                                                )
-                                            } else {
-                                                filter_by_date_dmy(&task.deadline.date, &input_date)
-                                            }
-                                        })
-                                        .collect::<Vec<_>>(),
-                                };
-                                filtered_by_switch
-                            }
-
-                            None => {
-                                tasks_clone.sort_by_key(|task| task.deadline.date);
-                                tasks_clone
-                            }
+                    "deadline" => match args {
+                        Some(args) => {
+                            filter_by_date(tasks_clone, &args, |task| Some(task.deadline_date()))?
                         }
-                        // match args {
-                        //     Some(_) => {
-                        //         return Err("This switch does not take any additional arguments"
-                        //             .to_string())
-                        //     }
-                        //     None => tasks_clone.sort_by_key(|task| task.deadline.date),
-                        // }
-                    }
+                        None => {
+                            tasks_clone.sort_by_key(Task::deadline_date);
+                            tasks_clone
+                        }
+                    },
                     "date" => match args {
-                        Some(args) => {
-                            // handle sorting
-                            if args.join("") == "-asc" || args.join("") == "-desc" {
-                                let sorting_method = args.join("");
-                                // performance-wise this is better then filtering by one method and the reversing accordingly
-                                match sorting_method.as_str() {
-                                    "-asc" => {
-                                        tasks_clone.sort_by(|task_a, task_b| {
-                                            let date_a = match task_a.status {
-                                                TaskStatus::Postponed(date)
-                                                | TaskStatus::Expired(date)
-                                                | TaskStatus::Aborted(date) => date,
-                                                _ => task_b.deadline.date,
-                                            };
-                                            let date_b = match task_b.status {
-                                                TaskStatus::Postponed(date)
-                                                | TaskStatus::Expired(date)
-                                                | TaskStatus::Aborted(date) => date,
-                                                _ => task_b.deadline.date,
-                                            };
-                                            date_a.cmp(&date_b)
-                                        });
-                                    }
-                                    "-desc" => {
-                                        tasks_clone.sort_by(|task_a, task_b| {
-                                            let date_a = match task_a.status {
-                                                TaskStatus::Postponed(date)
-                                                | TaskStatus::Expired(date)
-                                                | TaskStatus::Aborted(date) => date,
-                                                _ => task_a.deadline.date,
-                                            };
-                                            let date_b = match task_b.status {
-                                                TaskStatus::Postponed(date)
-                                                | TaskStatus::Expired(date)
-                                                | TaskStatus::Aborted(date) => date,
-                                                _ => task_b.deadline.date,
-                                            };
-                                            date_b.cmp(&date_a)
-                                        });
-                                    }
-                                    _ => return Err("Sorting method does not exists".to_string()),
-                                }
-
+                        Some(args) => match args.join("").as_str() {
+                            "-asc" => {
+                                tasks_clone.sort_by_key(Task::effective_date);
                                 tasks_clone
                             }
-                            // handle filtering
-                            else {
-                                let method = &args[0..1].join("");
-
-                                let filtering_method: Option<&str> = match method.as_str() {
-                                    "-gt" | "-lt" => Some(method),
-                                    _ => None,
-                                };
-
-                                let parsable_date_format = if filtering_method.is_some() {
-                                    args[1..].join(" ").to_string()
-                                } else {
-                                    args.join(" ").to_string()
-                                };
-
-                                let input_date = DateTime::parse_formated_string_to_datetime(
-                                    &parsable_date_format,
-                                    DateTime::date_now(),
-                                )?;
-
-                                // for performance
-                                let filtered_by_switch = match filtering_method {
-                                    Some(method) => match method {
-                                        "-gt" => tasks_clone
-                                            .into_iter()
-                                            .filter(|task| match task.status {
-                                                TaskStatus::Postponed(date)
-                                                | TaskStatus::Expired(date)
-                                                | TaskStatus::Aborted(date) => {
-                                                    filter_by_date_later_than(&date, &input_date)
-                                                }
-                                                _ => filter_by_date_later_than(
-                                                    &task.deadline.date,
-                                                    &input_date,
-                                                ),
-                                            })
-                                            .collect::<Vec<_>>(),
-                                        "-lt" => tasks_clone
-                                            .into_iter()
-                                            .filter(|task| match task.status {
-                                                TaskStatus::Postponed(date)
-                                                | TaskStatus::Expired(date)
-                                                | TaskStatus::Aborted(date) => {
-                                                    filter_by_date_earlier_than(&date, &input_date)
-                                                }
-                                                _ => filter_by_date_earlier_than(
-                                                    &task.deadline.date,
-                                                    &input_date,
-                                                ),
-                                            })
-                                            .collect::<Vec<_>>(),
-                                        _ => return Err("Inexistent filtering method".to_string()),
-                                    },
-                                    None => tasks_clone
-                                        .into_iter()
-                                        .filter(|task| {
-                                            let date_parts = parsable_date_format
-                                                .split_whitespace()
-                                                .collect::<Vec<_>>();
-
-                                            match task.status {
-                                                TaskStatus::Postponed(date)
-                                                | TaskStatus::Expired(date)
-                                                | TaskStatus::Aborted(date) => {
-                                                    if date_parts.len() == 2 {
-                                                        filter_by_date_equally(&date, &input_date)
-                                                    } else {
-                                                        filter_by_date_dmy(&date, &input_date)
-                                                    }
-                                                }
-                                                _ => {
-                                                    if date_parts.len() == 2 {
-                                                        filter_by_date_equally(
-                                                            &task.deadline.date,
-                                                            &input_date,
-                                                        )
-                                                    } else {
-                                                        filter_by_date_dmy(
-                                                            &task.deadline.date,
-                                                            &input_date,
-                                                        )
-                                                    }
-                                                }
-                                            }
-                                        })
-                                        .collect::<Vec<_>>(),
-                                };
-
-                                filtered_by_switch
+                            "-desc" => {
+                                tasks_clone.sort_by_key(|task| std::cmp::Reverse(task.effective_date()));
+                                tasks_clone
                             }
-                        }
+                            _ => filter_by_date(tasks_clone, &args, |task| {
+                                Some(task.effective_date())
+                            })?,
+                        },
                         None => {
                             // sort by date by default
-                            tasks_clone.sort_by_key(|task| match task.status {
-                                TaskStatus::Postponed(date)
-                                | TaskStatus::Expired(date)
-                                | TaskStatus::Aborted(date) => date,
-                                _ => task.deadline.date,
-                            });
+                            tasks_clone.sort_by_key(Task::effective_date);
+                            tasks_clone
+                        }
+                    },
+                    "note" => {
+                        let text = args.unwrap().join(" ");
+
+                        tasks_clone
+                            .into_iter()
+                            .filter(|task| notes::contains(task, &text))
+                            .collect::<Vec<_>>()
+                    }
+                    "created" => match args {
+                        Some(args) => filter_by_date(tasks_clone, &args, Task::created_date)?,
+                        None => {
+                            tasks_clone.sort_by_key(Task::created_date);
+                            tasks_clone
+                        }
+                    },
+                    "modified" => match args {
+                        Some(args) => filter_by_date(tasks_clone, &args, Task::modified_date)?,
+                        None => {
+                            tasks_clone.sort_by_key(Task::modified_date);
                             tasks_clone
                         }
                     },
+                    "sort" => {
+                        sort_tasks(&mut tasks_clone, &args.unwrap_or_default())?;
+                        tasks_clone
+                    }
+                    "tag" => {
+                        let tag = args
+                            .unwrap()
+                            .join("")
+                            .trim_start_matches('#')
+                            .to_lowercase();
+
+                        tasks_clone
+                            .into_iter()
+                            .filter(|task| task.tags().contains(&tag))
+                            .collect::<Vec<_>>()
+                    }
+                    "overdue" => {
+                        let now = DateTime::date_now();
+                        tasks_clone
+                            .into_iter()
+                            .filter(|task| task.is_overdue(&now))
+                            .collect::<Vec<_>>()
+                    }
                     "alphabetical" | "alph" => {
                         tasks_clone
                             .sort_by(|a, b| a.thing.to_lowercase().cmp(&b.thing.to_lowercase()));
@@ -1237,73 +2305,276 @@ pub mod cli_manager {
                                     .map_err(|err| err.to_string())?
                             }
                         };
-                        for task in tasks {
-                            file.write_fmt(format_args!("{task}\n"))
-                                .map_err(|err| err.to_string())?;
-                            file.flush().expect("Failed to flush buffer");
-                        }
+                        let redirected = match output_options {
+                            Some(output_options) => output_options.page(tasks),
+                            None => tasks,
+                        };
+
+                        file.write_all(format.render_tasks(redirected).as_bytes())
+                            .map_err(|err| err.to_string())?;
+                        file.flush().expect("Failed to flush buffer");
 
                         tasks_clone
                     }
-                    "help" => {
-                        let help_message = r"Description: Display every task, optionally filter or sort the output. Switches can be chained, after usage of switch, stream is redirected and next switch operate on previous output
-Usage: show tasks | 1 [switch] [arguments]
-Output: UTF-8 encoded string
-
-    --thing <String> => show tasks that starts with this string in thing field
-
-    --status <TaskStatus> => show tasks with given status
-        --status postponed => match every task with status as postponed
-
-    --deadline [Formatted date string -> format: 10/06/2023 12:30 | 10/06/2023 | tomorrow 12:30 | today 12:30 | next 12:30 | tomorrow | next | today | 12:30] => sort tasks by deadline date
-        --deadline => sorts tasks by deadline from earliest date
-        --deadline tomorrow => show tasks with deadline that is tomorrow, ignoring hours:minutes
-        --deadline tomorrow 12:30 => show tasks with deadline as tomorrow 12:30
-            
-    --alphabetical | --alph => sort tasks in alphabetical order by thing field
-    
-    --date [Filtering method] [Formatted date string -> format: 10/06/2023 12:30 | 10/06/2023 | tomorrow 12:30 | today 12:30 | next 12:30 | tomorrow | next | today | 12:30] => filter tasks by date, filters by postponed date, if present, or deadline
-        --date tomorrow => filters tasks equal provided d/m/y date part     
-        --date tomorrow 12:30 => filters tasks equal to provided date 
-        --date -gt 10/06/2023 12:30 => filters tasks by date later than given date
-        --date -lt 10/06/2023 12:30 => filters tasks by date earlier than given date
-        --date -asc => sorts tasks by ascending date
-        --date -desc => sorts tasks by descending date
-
-    --redirect [filename] => redirect stream to given file, if file does not exists it's created, takes relative path of current execution path or absolute path. If file is not supplied, creates file named stream.txt in working directory
-";
-                        println!("{help_message}");
-                        return Ok(());
-                    }
                     _ => return Err("Inexsistent filtering method".to_string()),
                 };
 
+                let filtered_by_switch = if negated {
+                    tasks
+                        .iter()
+                        .filter(|task| !filtered_by_switch.iter().any(|x| x.label == task.label))
+                        .cloned()
+                        .collect::<Vec<_>>()
+                } else {
+                    filtered_by_switch
+                };
+
                 // We are slicing switch that just executed
                 let advanced_status_vector = switches[1..].to_vec();
 
                 // If there are no switches left, we're returning None
                 // and returning output
                 match advanced_status_vector.len() {
-                    0 => show_tasks(&filtered_by_switch, None)?,
-                    _ => show_tasks(&filtered_by_switch, Some(advanced_status_vector))?,
-                };
+                    0 => filter_and_redirect(&filtered_by_switch, None, format, output_options),
+                    _ => filter_and_redirect(
+                        &filtered_by_switch,
+                        Some(advanced_status_vector),
+                        format,
+                        output_options,
+                    ),
+                }
             }
-            None => {
-                if tasks.is_empty() {
-                    println!("No available tasks");
-                } else {
-                    for task in tasks {
-                        println!("{task}");
+            None => Ok(tasks.to_vec()),
+        }
+    }
+
+    /// Output switches that apply to the final result of filtering, regardless of their position
+    struct OutputOptions {
+        offset: usize,
+        limit: Option<usize>,
+        tail: Option<usize>,
+        use_pager: bool,
+        // table on the terminal and plain otherwise, if not given
+        format: Option<OutputFormat>,
+    }
+
+    impl OutputOptions {
+        const DEFAULT_HEAD_TAIL: usize = 10;
+
+        fn from_switches(switches: Option<Switches>) -> Result<(Self, Option<Switches>), String> {
+            let mut output_options = OutputOptions {
+                offset: 0,
+                limit: None,
+                tail: None,
+                use_pager: true,
+                format: None,
+            };
+
+            let switches = match switches {
+                Some(switches) => switches,
+                None => return Ok((output_options, None)),
+            };
+
+            let mut rest = vec![];
+
+            for (switch, args) in switches {
+                let count = |default: Option<usize>| -> Result<usize, String> {
+                    match (&args, default) {
+                        (Some(args), _) => args.join("").parse::<usize>().map_err(|_| {
+                            format!("switch {switch} takes a non-negative number as an argument")
+                        }),
+                        (None, Some(default)) => Ok(default),
+                        (None, None) => {
+                            Err(format!("switch {switch} requiers additional arguments"))
+                        }
                     }
+                };
+
+                match switch.to_lowercase().trim_start_matches("--") {
+                    "limit" => output_options.limit = Some(count(None)?),
+                    "offset" => output_options.offset = count(None)?,
+                    "head" => output_options.limit = Some(count(Some(Self::DEFAULT_HEAD_TAIL))?),
+                    "tail" => output_options.tail = Some(count(Some(Self::DEFAULT_HEAD_TAIL))?),
+                    "no-pager" => output_options.use_pager = false,
+                    "format" => match &args {
+                        Some(args) => {
+                            output_options.format = Some(OutputFormat::parse(&args.join(""))?)
+                        }
+                        None => {
+                            return Err(format!("switch {switch} requiers additional arguments"))
+                        }
+                    },
+                    _ => rest.push((switch, args)),
                 }
             }
+
+            let rest = if rest.is_empty() { None } else { Some(rest) };
+
+            Ok((output_options, rest))
+        }
+
+        fn is_paginated(&self) -> bool {
+            self.offset > 0 || self.limit.is_some() || self.tail.is_some()
+        }
+
+        fn page<'a>(&self, tasks: &'a [Task]) -> &'a [Task] {
+            let tasks = &tasks[self.offset.min(tasks.len())..];
+
+            let tasks = match self.tail {
+                Some(tail) => &tasks[tasks.len().saturating_sub(tail)..],
+                None => tasks,
+            };
+
+            match self.limit {
+                Some(limit) => &tasks[..limit.min(tasks.len())],
+                None => tasks,
+            }
+        }
+    }
+
+    fn print_tasks(tasks: &[Task], output_options: &OutputOptions) -> Result<(), String> {
+        let format = output_options
+            .format
+            .unwrap_or(if io::stdout().is_terminal() {
+                OutputFormat::Table
+            } else {
+                OutputFormat::Plain
+            });
+
+        if tasks.is_empty() && !format.is_machine_readable() {
+            println!("No available tasks");
+            return Ok(());
+        }
+
+        let page = output_options.page(tasks);
+
+        let mut output = format.render_tasks_for_terminal(page);
+
+        if output_options.is_paginated() || page.len() != tasks.len() {
+            let summary = format!("Showing {} of {} tasks", page.len(), tasks.len());
+
+            // keep the machine readable output parsable
+            if format.is_machine_readable() {
+                eprintln!("{summary}");
+            } else {
+                output.push_str(&format!("{summary}\n"));
+            }
+        }
+
+        if output_options.use_pager && output_exceeds_terminal(&output) {
+            if let Ok(()) = print_through_pager(&output) {
+                return Ok(());
+            }
+        }
+
+        print!("{output}");
+
+        Ok(())
+    }
+
+    fn output_exceeds_terminal(output: &str) -> bool {
+        if !io::stdout().is_terminal() {
+            return false;
+        }
+
+        match terminal_height() {
+            Some(height) => output.lines().count() >= height,
+            None => false,
+        }
+    }
+
+    fn terminal_height() -> Option<usize> {
+        if let Some(lines) = std::env::var("LINES").ok().and_then(|x| x.parse().ok()) {
+            return Some(lines);
+        }
+
+        terminal_size().map(|(rows, _)| rows)
+    }
+
+    pub(crate) fn terminal_width() -> Option<usize> {
+        if let Some(columns) = std::env::var("COLUMNS").ok().and_then(|x| x.parse().ok()) {
+            return Some(columns);
+        }
+
+        terminal_size().map(|(_, columns)| columns)
+    }
+
+    fn terminal_size() -> Option<(usize, usize)> {
+        // stty reads the size of terminal attached to stdin, outputs "rows columns"
+        let output = std::process::Command::new("stty")
+            .arg("size")
+            .stdin(std::process::Stdio::inherit())
+            .output()
+            .ok()?;
+
+        let output = String::from_utf8_lossy(&output.stdout);
+        let mut size = output
+            .split_whitespace()
+            .filter_map(|value| value.parse().ok());
+
+        Some((size.next()?, size.next()?))
+    }
+
+    /// Uses $PAGER if set, otherwise less or more on windows
+    fn print_through_pager(output: &str) -> Result<(), Box<dyn Error>> {
+        let pager = match std::env::var("PAGER") {
+            Ok(pager) if !pager.trim().is_empty() => pager,
+            _ if cfg!(target_os = "windows") => "more".to_string(),
+            _ => "less -R".to_string(),
         };
+
+        let mut pager = pager.split_whitespace();
+        let mut child = std::process::Command::new(pager.next().unwrap_or("less"))
+            .args(pager)
+            .stdin(std::process::Stdio::piped())
+            .spawn()?;
+
+        if let Some(mut stdin) = child.stdin.take() {
+            // pager closed before reading everything is not an error
+            let _ = stdin.write_all(output.as_bytes());
+        }
+
+        child.wait()?;
+
         Ok(())
     }
 
+    /// Filtering switches narrow down the tasks, the rest sorts or redirects them
+    fn is_filtering_switch(switch: &str, args: &Option<Vec<&str>>) -> bool {
+        match switch {
+            "thing" | "status" | "tag" | "note" | "overdue" => true,
+            "deadline" | "created" | "modified" => args.is_some(),
+            "date" => match args {
+                Some(args) => !matches!(args.join("").as_str(), "-asc" | "-desc"),
+                None => false,
+            },
+            _ => false,
+        }
+    }
+
+    /// Statuses given as comma separated list, e.g. todo,postponed
+    fn parse_statuses(args: &[&str]) -> Result<Vec<String>, String> {
+        let statuses = args
+            .join(",")
+            .split(',')
+            .map(|status| status.trim().to_lowercase())
+            .filter(|status| !status.is_empty())
+            .collect::<Vec<_>>();
+
+        match statuses
+            .iter()
+            .find(|status| !STATUS_NAMES.contains(&status.as_str()))
+        {
+            Some(status) => Err(format!("No such status available: {status}")),
+            None => Ok(statuses),
+        }
+    }
+
+    pub const STATUS_NAMES: [&str; 5] = ["completed", "todo", "postponed", "expired", "aborted"];
+
     pub fn match_status(task: &Task, status: &str) -> bool {
         match status {
-            "completed" => matches!(task.status, TaskStatus::Completed),
+            "completed" => matches!(task.status, TaskStatus::Completed(_)),
             "todo" => matches!(task.status, TaskStatus::Todo),
             "postponed" => matches!(task.status, TaskStatus::Postponed(_)),
             "expired" => matches!(task.status, TaskStatus::Expired(_)),
@@ -1313,15 +2584,11 @@ Output: UTF-8 encoded string
     }
 
     pub fn show_user_actions() {
-        println!(
-            "Available actions:\n{}{}{}{}{}{}",
-            "1 | show tasks => Display all tasks\n",
-            "2 | add task | add => Add new task (thing, deadline) \n",
-            "3 | edit task | edit => Edit task <Task id> [--field] \n",
-            "4 | delete task | delete | delete all => Detete Task <Task id | all>\n",
-            "help => Display this help message\n",
-            "exit => Terminates current procces\n",
-        )
+        println!("{}", commands::interactive_usage());
+    }
+
+    pub fn show_command_line_usage() {
+        print!("{}", commands::usage());
     }
 
     pub fn clear_console() {
@@ -1341,15 +2608,145 @@ Output: UTF-8 encoded string
         cli_manager::clear_console();
         cli_manager::show_user_actions();
     }
+
+    #[cfg(test)]
+    mod tests {
+        use super::*;
+
+        fn task(tasks: &[Task], thing: &str, deadline: &str, created: Option<&str>) -> Task {
+            let mut task = Task::new_task(tasks, thing, Deadline::new(&deadline.to_string()).unwrap());
+            task.created = created.map(|created| Deadline::new(&created.to_string()).unwrap().date);
+            task
+        }
+
+        fn things(tasks: &[Task]) -> Vec<&str> {
+            tasks.iter().map(Task::unquoted_thing).collect()
+        }
+
+        fn date(date: &str) -> DateTime<Local> {
+            parse_filter_date(&date.split_whitespace().collect::<Vec<_>>()).unwrap()
+        }
+
+        fn matches(filter: &str, date_to_match: &str) -> bool {
+            DateFilter::parse(&filter.split_whitespace().collect::<Vec<_>>())
+                .unwrap()
+                .matches(&date(date_to_match))
+        }
+
+        #[test]
+        fn date_only_bounds_cover_the_whole_day() {
+            assert!(!matches("-gt 10/06/2030", "10/06/2030 23:59"));
+            assert!(matches("-gt 10/06/2030", "11/06/2030 00:00"));
+
+            assert!(!matches("-ge 10/06/2030", "09/06/2030 23:59"));
+            assert!(matches("-ge 10/06/2030", "10/06/2030 00:00"));
+
+            assert!(matches("-lt 10/06/2030", "09/06/2030 23:59"));
+            assert!(!matches("-lt 10/06/2030", "10/06/2030 00:00"));
+
+            assert!(matches("-le 10/06/2030", "10/06/2030 23:59"));
+            assert!(!matches("-le 10/06/2030", "11/06/2030 00:00"));
+
+            assert!(!matches("10/06/2030", "09/06/2030 23:59"));
+            assert!(matches("10/06/2030", "10/06/2030 23:59"));
+            assert!(!matches("10/06/2030", "11/06/2030 00:00"));
+
+            assert!(!matches("-between 10/06/2030 12/06/2030", "09/06/2030 23:59"));
+            assert!(matches("-between 10/06/2030 12/06/2030", "12/06/2030 23:59"));
+            assert!(!matches("-between 10/06/2030 12/06/2030", "13/06/2030 00:00"));
+        }
+
+        #[test]
+        fn bounds_with_time_cover_the_whole_minute() {
+            let within_minute = date("10/06/2030 12:30") + Duration::seconds(59);
+
+            assert!(!matches("-gt 10/06/2030 12:30", "10/06/2030 12:30"));
+            assert!(matches("-gt 10/06/2030 12:30", "10/06/2030 12:31"));
+
+            assert!(!matches("-ge 10/06/2030 12:30", "10/06/2030 12:29"));
+            assert!(matches("-ge 10/06/2030 12:30", "10/06/2030 12:30"));
+
+            assert!(matches("-lt 10/06/2030 12:30", "10/06/2030 12:29"));
+            assert!(!matches("-lt 10/06/2030 12:30", "10/06/2030 12:30"));
+
+            let le = DateFilter::parse(&["-le", "10/06/2030", "12:30"]).unwrap();
+            assert!(le.matches(&within_minute));
+            assert!(!matches("-le 10/06/2030 12:30", "10/06/2030 12:31"));
+
+            assert!(matches("10/06/2030 12:30", "10/06/2030 12:30"));
+            assert!(!matches("10/06/2030 12:30", "10/06/2030 12:31"));
+        }
+
+        #[test]
+        fn window_includes_bounds_of_le_and_ge() {
+            let window = |filter: &str| {
+                DateFilter::parse(&filter.split_whitespace().collect::<Vec<_>>())
+                    .unwrap()
+                    .window()
+            };
+
+            assert_eq!(window("-ge 10/06/2030"), (Some(date("10/06/2030")), None));
+            assert_eq!(window("-gt 10/06/2030"), (Some(date("11/06/2030")), None));
+            assert_eq!(window("-le 10/06/2030"), (None, Some(date("11/06/2030"))));
+            assert_eq!(window("-lt 10/06/2030"), (None, Some(date("10/06/2030"))));
+            assert_eq!(
+                window("-le 10/06/2030 12:30"),
+                (None, Some(date("10/06/2030 12:31")))
+            );
+        }
+
+        #[test]
+        fn sort_keeps_tasks_without_date_first_and_ties_in_order() {
+            let mut tasks = vec![];
+            for (thing, created) in [
+                ("old", Some("01/01/2020")),
+                ("undated", None),
+                ("new", Some("01/01/2021")),
+                ("same as new", Some("01/01/2021")),
+            ] {
+                let task = task(&tasks, thing, "01/01/2030", created);
+                tasks.push(task);
+            }
+
+            sort_tasks(&mut tasks, &["created:desc"]).unwrap();
+            assert_eq!(things(&tasks), ["undated", "new", "same as new", "old"]);
+
+            sort_tasks(&mut tasks, &["created"]).unwrap();
+            assert_eq!(things(&tasks), ["undated", "old", "new", "same as new"]);
+        }
+    }
 }
 
 pub mod tasks_file_manager {
     use super::*;
+    use std::cell::RefCell;
     use std::env;
     use std::path::PathBuf;
+    use std::sync::Once;
+
+    static STORAGE_NOTICE: Once = Once::new();
+
+    thread_local! {
+        // directory used instead of the default storage, so tests of one process do not share files
+        static STORAGE_DIR: RefCell<Option<PathBuf>> = const { RefCell::new(None) };
+    }
+
+    /// Runs f with the files stored in given directory, only on the current thread
+    #[cfg(test)]
+    pub(crate) fn with_storage_dir<T>(dir: &Path, f: impl FnOnce() -> T) -> T {
+        let previous = STORAGE_DIR.with(|storage| storage.replace(Some(dir.to_path_buf())));
+        let result = std::panic::catch_unwind(std::panic::AssertUnwindSafe(f));
+        STORAGE_DIR.with(|storage| storage.replace(previous));
+
+        result.unwrap_or_else(|panic| std::panic::resume_unwind(panic))
+    }
 
     // filename with extension
     pub fn make_file_path_in_working_dir(filename: &str) -> Result<PathBuf, Box<dyn Error>> {
+        if let Some(dir) = STORAGE_DIR.with(|storage| storage.borrow().clone()) {
+            return Ok(dir.join(filename));
+        }
+
         let dir_path = match std::env::var("SystemDrive") {
             Ok(system_drive_letter) => {
                 let username = whoami::username();
@@ -1360,14 +2757,16 @@ pub mod tasks_file_manager {
                 PathBuf::from(path)
             }
             Err(err) => {
-                eprintln!("Error while reading SystemDrive env var with error: {err}");
-
                 let working_dir = env::current_dir()?;
 
-                println!(
-                    "Using current directory as the storage for tasks: {}",
-                    working_dir.display()
-                );
+                // stdout is reserved for the output of the command, the notice is printed once per run
+                STORAGE_NOTICE.call_once(|| {
+                    eprintln!("Error while reading SystemDrive env var with error: {err}");
+                    eprintln!(
+                        "Using current directory as the storage for tasks: {}",
+                        working_dir.display()
+                    );
+                });
 
                 working_dir
             }
@@ -1383,9 +2782,20 @@ pub mod tasks_file_manager {
         Ok(file_path)
     }
 
+    /// Saves the history first, so the change of the task is never saved without its previous version
+    pub fn save(tasks: &[Task], tasks_history: &mut HashMap<String, Vec<HistoryEntry>>) {
+        if let Err(err) = save_tasks_history(tasks, tasks_history) {
+            eprintln!("{err}");
+        }
+
+        if let Err(err) = save_tasks(tasks) {
+            eprintln!("{err}");
+        }
+    }
+
     pub fn save_tasks_history(
-        tasks: &Vec<Task>,
-        tasks_history: &mut HashMap<String, Vec<Task>>,
+        tasks: &[Task],
+        tasks_history: &mut HashMap<String, Vec<HistoryEntry>>,
     ) -> Result<(), Box<dyn Error>> {
         let file_path = make_file_path_in_working_dir("tasks_history.txt")?;
 
@@ -1400,12 +2810,9 @@ pub mod tasks_file_manager {
             return Ok(());
         }
 
-        for (_, tasks) in tasks_history.iter() {
-            for task in tasks {
-                file.write_fmt(format_args!(
-                    "Task {{ thing: {}, status: {}, label: {}, deadline: {:?} }}\n",
-                    task.thing, task.status, task.label, task.deadline
-                ))?;
+        for (_, entries) in tasks_history.iter() {
+            for entry in entries {
+                writeln!(file, "{}", history::format_entry_line(entry))?;
                 file.flush().expect("Failed to flush buffer");
             }
         }
@@ -1413,7 +2820,7 @@ pub mod tasks_file_manager {
         Ok(())
     }
 
-    pub fn save_tasks(tasks: &Vec<Task>) -> Result<(), Box<dyn Error>> {
+    pub fn save_tasks(tasks: &[Task]) -> Result<(), Box<dyn Error>> {
         let file_path = make_file_path_in_working_dir("tasks.txt")?;
 
         let mut file = OpenOptions::new()
@@ -1428,10 +2835,7 @@ pub mod tasks_file_manager {
         }
 
         for task in tasks {
-            file.write_fmt(format_args!(
-                "Task {{ thing: {}, status: {}, label: {}, deadline: {:?} }}\n",
-                task.thing, task.status, task.label, task.deadline
-            ))?;
+            writeln!(file, "{}", format_task_line(task))?;
             file.flush().expect("Failed to flush buffer");
         }
 
@@ -1448,66 +2852,127 @@ pub mod tasks_file_manager {
 
         let instaces: Vec<Task> = reader
             .lines()
-            .map(|line| {
-                let line = line.unwrap();
-                let parsed = line.split("Task { ");
-                let parsed = parsed.filter(|x| x.len() > 0);
-                let parsed = parsed.collect::<String>();
+            .map(|line| parse_task_line(&line.unwrap()))
+            .collect::<Vec<Task>>();
 
-                let mut end = 0;
+        Ok(instaces)
+    }
 
-                // Handle parsing "thing" field separately
-                let delimited = parsed.split_inclusive(",").collect::<Vec<_>>();
+    /// History file written before the entries carried dates and users holds bare task lines, these are still read
+    pub fn get_saved_history(
+        tasks: &[Task],
+    ) -> Result<HashMap<String, Vec<HistoryEntry>>, Box<dyn Error>> {
+        let file_path = make_file_path_in_working_dir("tasks_history.txt")?;
 
-                for (idx, item) in delimited.iter().enumerate() {
-                    if item.trim().to_lowercase().contains("status") {
-                        end = idx;
-                        break;
-                    }
-                }
+        let entries = fs::read_to_string(file_path)?
+            .lines()
+            .filter(|line| !line.trim().is_empty())
+            .map(history::parse_entry_line)
+            .collect::<Result<Vec<_>, String>>()?;
+
+        Ok(history::group_by_label(entries, tasks))
+    }
 
-                let thing_key_value_str = &delimited[0..end].join("");
-                let thing_value = thing_key_value_str
-                    .split("thing: ")
-                    .collect::<Vec<_>>()
-                    .join("");
-                let thing_value = thing_value.trim_end_matches(",");
+    /// Line of the tasks file, the same format is written by format_task_line
+    pub fn parse_task_line(line: &str) -> Task {
+        let parsed = line.split("Task { ");
+        let parsed = parsed.filter(|x| x.len() > 0);
+        let parsed = parsed.collect::<String>();
 
-                let thing_key_value = format!("thing: {},", thing_value);
-                let thing_key_value = thing_key_value.as_str();
+        let mut end = 0;
 
-                let parsed = parsed.replace(thing_key_value, "");
-                let parsed = parsed
-                    .split(",")
-                    .map(|x| x.trim().split(": ").collect::<Vec<_>>())
-                    .collect::<Vec<Vec<&str>>>();
+        // Handle parsing "thing" field separately
+        let delimited = parsed.split_inclusive(",").collect::<Vec<_>>();
 
-                let mut instance_fields_hashmap: HashMap<String, String> =
-                    HashMap::from([("thing".to_string(), thing_value.to_string())]);
+        for (idx, item) in delimited.iter().enumerate() {
+            if item.trim().to_lowercase().contains("status") {
+                end = idx;
+                break;
+            }
+        }
 
-                parsed.iter().for_each(|x| {
-                    let field = x.get(0).unwrap().to_string();
-                    let value = x.get(1).unwrap().to_string();
+        let thing_key_value_str = &delimited[0..end].join("");
+        let thing_value = thing_key_value_str
+            .split("thing: ")
+            .collect::<Vec<_>>()
+            .join("");
+        let thing_value = thing_value.trim_end_matches(",");
 
-                    if field == "deadline" {
-                        let mut date = x.last().unwrap().split_whitespace();
-                        let date = date.next().unwrap();
+        let thing_key_value = format!("thing: {},", thing_value);
+        let thing_key_value = thing_key_value.as_str();
 
-                        instance_fields_hashmap.insert(field, date.trim().to_string());
-                    } else {
-                        instance_fields_hashmap.insert(field, value);
-                    }
-                });
+        let parsed = parsed.replace(thing_key_value, "");
+        let parsed = parsed
+            .split(",")
+            .map(|x| x.trim().split(": ").collect::<Vec<_>>())
+            .collect::<Vec<Vec<&str>>>();
 
-                parse_task_from_file(&mut instance_fields_hashmap)
-            })
-            .collect::<Vec<Task>>();
+        let mut instance_fields_hashmap: HashMap<String, String> =
+            HashMap::from([("thing".to_string(), thing_value.to_string())]);
 
-        Ok(instaces)
+        parsed.iter().for_each(|x| {
+            let field = x.get(0).unwrap().to_string();
+            let value = x.get(1).unwrap().to_string();
+
+            if field == "deadline" {
+                let mut date = x.last().unwrap().split_whitespace();
+                let date = date.next().unwrap();
+
+                instance_fields_hashmap.insert(field, date.trim().to_string());
+            } else {
+                instance_fields_hashmap.insert(field, value);
+            }
+        });
+
+        parse_task_from_file(&mut instance_fields_hashmap)
+    }
+
+    pub fn format_task_line(task: &Task) -> String {
+        // versions saved before tasks had uid are written without it
+        let uid = if task.uid.is_empty() {
+            String::new()
+        } else {
+            format!(", uid: {}", task.uid)
+        };
+
+        let timestamps = [("created", task.created), ("modified", task.modified)]
+            .into_iter()
+            .filter_map(|(field, date)| {
+                date.map(|date| {
+                    format!(", {field}: {}", date.to_rfc3339_opts(SecondsFormat::Secs, false))
+                })
+            })
+            .collect::<String>();
+
+        format!(
+            "Task {{ thing: {}, status: {}, label: {}{uid}{timestamps}{}{}{}{}, deadline: {:?} }}",
+            task.thing,
+            task.status,
+            task.label,
+            notes::format_fields(task),
+            attachments::format_field(task),
+            timer::format_field(task),
+            estimates::format_field(task),
+            task.deadline
+        )
     }
 
     pub fn parse_task_from_file(instance_fields: &mut HashMap<String, String>) -> Task {
         let deadline_date = instance_fields.remove("deadline").unwrap();
+        let deadline = Deadline {
+            date: if DateTime::is_valid_date_format(&deadline_date) {
+                match DateTime::parse_string_to_datetime_local(&deadline_date) {
+                    Ok(date) => date,
+                    Err(err) => self::panic!("{err}"),
+                }
+            } else {
+                match DateTime::parse_from_rfc3339(&deadline_date) {
+                    Ok(date) => date.with_timezone(&Local),
+                    Err(err) => self::panic!("{err}"),
+                }
+            },
+        };
+
         Task {
             thing: instance_fields.remove("thing").unwrap().to_string(),
             status: match instance_fields
@@ -1517,7 +2982,8 @@ pub mod tasks_file_manager {
                 .to_string()
                 .as_str()
             {
-                "completed" => TaskStatus::Completed,
+                // tasks completed before the date of completion was kept get their deadline, so old data filters by --date as before
+                "completed" => TaskStatus::Completed(deadline.date),
                 "todo" => TaskStatus::Todo,
                 other_status => {
                     let parted = other_status.split("(").collect::<Vec<_>>();
@@ -1526,6 +2992,9 @@ pub mod tasks_file_manager {
                         let date = date.trim_end_matches(")");
 
                         match status.to_lowercase().to_string().as_str() {
+                            "completed" => {
+                                TaskStatus::Completed(parse_date_inside_task_status(date))
+                            }
                             "postponed" => {
                                 TaskStatus::Postponed(parse_date_inside_task_status(date))
                             }
@@ -1538,20 +3007,36 @@ pub mod tasks_file_manager {
                     }
                 }
             },
-            deadline: Deadline {
-                date: if DateTime::is_valid_date_format(&deadline_date) {
-                    match DateTime::parse_string_to_datetime_local(&deadline_date) {
-                        Ok(date) => date,
-                        Err(err) => self::panic!("{err}"),
-                    }
-                } else {
-                    match DateTime::parse_from_rfc3339(&deadline_date) {
-                        Ok(date) => date.with_timezone(&Local),
-                        Err(err) => self::panic!("{err}"),
-                    }
-                },
-            },
+            deadline,
             label: instance_fields.remove("label").unwrap().to_string(),
+            uid: instance_fields.remove("uid").unwrap_or_default(),
+            created: instance_fields
+                .remove("created")
+                .filter(|date| date != "unknown")
+                .map(|date| parse_date_inside_task_status(&date)),
+            modified: instance_fields
+                .remove("modified")
+                .filter(|date| date != "unknown")
+                .map(|date| parse_date_inside_task_status(&date)),
+            note: instance_fields
+                .remove("note")
+                .map(|note| notes::decode(&note))
+                .unwrap_or_default(),
+            annotations: instance_fields
+                .remove("annotations")
+                .map(|annotations| notes::parse_annotations(&annotations))
+                .unwrap_or_default(),
+            attachments: instance_fields
+                .remove("attachments")
+                .map(|attachments| attachments::parse_field(&attachments))
+                .unwrap_or_default(),
+            intervals: instance_fields
+                .remove("intervals")
+                .map(|intervals| timer::parse_field(&intervals))
+                .unwrap_or_default(),
+            estimate: instance_fields
+                .remove("estimate")
+                .and_then(|estimate| estimates::parse_field(&estimate)),
         }
     }
 
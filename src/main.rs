@@ -3,7 +3,7 @@
 pub mod utils;
 
 use rutodo::tasks_file_manager;
-use rutodo::{spawn_cli_interface, Task};
+use rutodo::{spawn_cli_interface, HistoryEntry, Task};
 use std::collections::HashMap;
 use std::fs::OpenOptions;
 use std::io::{Read, Write};
@@ -17,35 +17,31 @@ fn main() -> Result<(), Box<dyn std::error::Error>> {
     };
     let mut tasks: Vec<Task> = Vec::new();
 
-    // Task clone
-    let mut tasks_history: HashMap<String, Vec<Task>> = HashMap::new();
+    let mut tasks_history: HashMap<String, Vec<HistoryEntry>> = HashMap::new();
 
     match tasks_file_manager::get_saved_tasks("tasks.txt") {
         Ok(mut instances) => {
             if !instances.is_empty() {
-                Task::mark_expired_tasks_as_status_expired(&mut instances);
+                Task::assign_missing_uids(&mut instances);
                 instances.into_iter().for_each(|x| tasks.push(x));
             }
         }
         Err(err) => eprintln!("{err}"),
     };
 
-    match tasks_file_manager::get_saved_tasks("tasks_history.txt") {
-        Ok(instances) => {
-            if !instances.is_empty() {
-                for task in instances {
-                    tasks_history
-                        .entry(task.label.clone())
-                        .or_default()
-                        .push(task);
-                }
-            }
-        }
+    match tasks_file_manager::get_saved_history(&tasks) {
+        Ok(history) => tasks_history = history,
         Err(err) => eprintln!("{err}"),
     }
 
+    // history is linked to the tasks as they were saved, so expiration is its own change
+    if !tasks.is_empty() {
+        Task::mark_expired_tasks_as_status_expired(&mut tasks, &mut tasks_history);
+    }
+
     if let Err(interface_err) = spawn_cli_interface(&mut tasks, &mut tasks_history) {
-        eprintln!("{interface_err}")
+        eprintln!("{interface_err}");
+        std::process::exit(1);
     }
 
     Ok(())
@@ -0,0 +1,190 @@
+use crate::history::{self, ChangeKind};
+use crate::{tasks_file_manager, DateTimeFormatter, HistoryEntry, Task};
+use chrono::{DateTime, Local, SecondsFormat};
+use std::collections::HashMap;
+
+/// Timestamped remark added to the task, e.g. waiting on vendor reply
+#[derive(Debug, Clone, PartialEq)]
+pub struct Annotation {
+    pub at: DateTime<Local>,
+    pub text: String,
+}
+
+/// Appends the annotation to the task, it is recorded in the history and the log
+pub fn annotate(
+    tasks: &mut [Task],
+    tasks_history: &mut HashMap<String, Vec<HistoryEntry>>,
+    task_label_number: &str,
+    text: &str,
+) -> Result<(), String> {
+    let text = text.trim();
+    // quotes are kept by the interactive prompt
+    let text = text
+        .strip_prefix('"')
+        .and_then(|text| text.strip_suffix('"'))
+        .unwrap_or(text)
+        .trim();
+
+    if text.is_empty() {
+        return Err("Annotation cannot be empty".to_string());
+    }
+
+    let task = Task::find_by_label_number(tasks, task_label_number)?;
+    let prev_task = task.clone();
+
+    task.annotations.push(Annotation {
+        at: Task::timestamp(),
+        text: text.to_string(),
+    });
+
+    history::record(tasks_history, ChangeKind::Annotate, prev_task, task);
+
+    println!("Annotated task:\n{task}{}", describe(task));
+
+    tasks_file_manager::save(tasks, tasks_history);
+
+    Ok(())
+}
+
+/// Replaces the note of the task, \n in the text starts a new line and empty text removes the note
+pub fn set_note(
+    tasks: &mut [Task],
+    tasks_history: &mut HashMap<String, Vec<HistoryEntry>>,
+    task_label_number: &str,
+    note: &str,
+) -> Result<(), String> {
+    let task = Task::find_by_label_number(tasks, task_label_number)?;
+    let prev_task = task.clone();
+
+    task.note = note.replace("\\n", "\n").trim().to_string();
+
+    if *task == prev_task {
+        println!("Nothing was changed");
+        return Ok(());
+    }
+
+    history::record(tasks_history, ChangeKind::Edit, prev_task, task);
+
+    println!("Updated task:\n{task}{}", describe(task));
+
+    tasks_file_manager::save(tasks, tasks_history);
+
+    Ok(())
+}
+
+/// Note and annotations printed under the task, empty if the task has none
+pub fn describe(task: &Task) -> String {
+    let mut description = String::new();
+
+    if !task.note.is_empty() {
+        description.push_str("Note:\n");
+        for line in task.note.lines() {
+            description.push_str(&format!("    {line}\n"));
+        }
+    }
+
+    if !task.annotations.is_empty() {
+        description.push_str("Annotations:\n");
+        for annotation in &task.annotations {
+            description.push_str(&format!(
+                "    {} {}\n",
+                DateTime::date_user_formating(annotation.at),
+                annotation.text
+            ));
+        }
+    }
+
+    description
+}
+
+/// Note or any of the annotations contains the text, case is ignored
+pub fn contains(task: &Task, text: &str) -> bool {
+    let text = text.to_lowercase();
+
+    task.note.to_lowercase().contains(&text)
+        || task
+            .annotations
+            .iter()
+            .any(|annotation| annotation.text.to_lowercase().contains(&text))
+}
+
+// Task is stored on one line split by commas and ": ", so these characters are percent-encoded:
+// note: first line%0Asecond line, annotations: 2026-10-19T10:00:00+02:00 waiting on vendor reply;...
+pub fn encode(text: &str) -> String {
+    let mut encoded = String::with_capacity(text.len());
+
+    for c in text.chars() {
+        match c {
+            '%' | ',' | ':' | ';' | '{' | '}' | '\n' | '\r' => {
+                encoded.push_str(&format!("%{:02X}", c as u32))
+            }
+            c => encoded.push(c),
+        }
+    }
+
+    encoded
+}
+
+pub fn decode(text: &str) -> String {
+    let mut decoded = String::with_capacity(text.len());
+    let mut chars = text.chars();
+
+    while let Some(c) = chars.next() {
+        if c != '%' {
+            decoded.push(c);
+            continue;
+        }
+
+        let code = chars.by_ref().take(2).collect::<String>();
+
+        match u8::from_str_radix(&code, 16) {
+            Ok(byte) => decoded.push(byte as char),
+            // not written by encode, kept as it is
+            Err(_) => decoded.push_str(&format!("%{code}")),
+        }
+    }
+
+    decoded
+}
+
+/// Fields of the task line, nothing is written for the task without a note and annotations
+pub fn format_fields(task: &Task) -> String {
+    let mut fields = String::new();
+
+    if !task.note.is_empty() {
+        fields.push_str(&format!(", note: {}", encode(&task.note)));
+    }
+
+    if !task.annotations.is_empty() {
+        let annotations = task
+            .annotations
+            .iter()
+            .map(|annotation| {
+                format!(
+                    "{} {}",
+                    annotation.at.to_rfc3339_opts(SecondsFormat::Secs, false),
+                    encode(&annotation.text)
+                )
+            })
+            .collect::<Vec<_>>()
+            .join(";");
+
+        fields.push_str(&format!(", annotations: {annotations}"));
+    }
+
+    fields
+}
+
+pub fn parse_annotations(annotations: &str) -> Vec<Annotation> {
+    annotations
+        .split(';')
+        .filter_map(|annotation| {
+            let (at, text) = annotation.trim().split_once(' ')?;
+
+            Some(Annotation {
+                at: DateTime::parse_from_rfc3339(at).ok()?.with_timezone(&Local),
+                text: decode(text),
+            })
+        })
+        .collect()
+}
@@ -0,0 +1,344 @@
+use crate::{cli_manager, timer, DateTimeFormatter, Task, TaskStatus};
+use chrono::{DateTime, Local, SecondsFormat};
+use std::io::{self, IsTerminal};
+
+/// Format of tasks printed by show tasks, show task and written with --redirect
+///
+/// Machine readable formats share one schema, every field is always present:
+///     label       => "Task 4"
+///     id          => 4
+///     thing       => "buy milk"
+///     status      => todo | completed | postponed | expired | aborted
+///     status_date => RFC 3339 date carried by completed, postponed, expired and aborted status, otherwise null (empty in csv/tsv)
+///     deadline    => RFC 3339 date
+///     created     => RFC 3339 date of creation, null for tasks saved before it was recorded
+///     modified    => RFC 3339 date of the last change, null for tasks saved before it was recorded
+///     note        => multi-line note, null if the task has none
+///     annotations => one annotation per line as "<RFC 3339 date> <text>", null if the task has none
+///     attachments => one absolute path or URL per line, null if the task has none
+///     estimate    => 90, estimate in minutes, null if the task has none
+///     tracked     => 45, minutes tracked including the running timer, null if no time was tracked
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum OutputFormat {
+    Plain,
+    Json,
+    Jsonl,
+    Csv,
+    Tsv,
+    Table,
+}
+
+const FIELDS: [&str; 13] = [
+    "label",
+    "id",
+    "thing",
+    "status",
+    "status_date",
+    "deadline",
+    "created",
+    "modified",
+    "note",
+    "annotations",
+    "attachments",
+    "estimate",
+    "tracked",
+];
+
+impl OutputFormat {
+    pub fn parse(format: &str) -> Result<Self, String> {
+        match format.to_lowercase().as_str() {
+            "plain" => Ok(OutputFormat::Plain),
+            "json" => Ok(OutputFormat::Json),
+            "jsonl" => Ok(OutputFormat::Jsonl),
+            "csv" => Ok(OutputFormat::Csv),
+            "tsv" => Ok(OutputFormat::Tsv),
+            "table" => Ok(OutputFormat::Table),
+            other => Err(format!(
+                "Unsupported format {other}, available formats: plain | json | jsonl | csv | tsv | table"
+            )),
+        }
+    }
+
+    /// Formats meant to be consumed by other programs, these should not be mixed with messages
+    pub fn is_machine_readable(&self) -> bool {
+        !matches!(self, OutputFormat::Plain | OutputFormat::Table)
+    }
+
+    pub fn render_tasks(&self, tasks: &[Task]) -> String {
+        match self {
+            OutputFormat::Plain => tasks.iter().map(|task| format!("{task}\n")).collect(),
+            OutputFormat::Json => {
+                let objects = tasks.iter().map(json_object).collect::<Vec<_>>();
+
+                if objects.is_empty() {
+                    "[]\n".to_string()
+                } else {
+                    format!("[\n  {}\n]\n", objects.join(",\n  "))
+                }
+            }
+            OutputFormat::Jsonl => tasks
+                .iter()
+                .map(|task| format!("{}\n", json_object(task)))
+                .collect(),
+            OutputFormat::Csv => separated_values(tasks, ",", csv_value),
+            OutputFormat::Tsv => separated_values(tasks, "\t", tsv_value),
+            OutputFormat::Table => table(tasks, None, false),
+        }
+    }
+
+    /// Table printed to the terminal is colored by status and long things are truncated to fit its width
+    pub fn render_tasks_for_terminal(&self, tasks: &[Task]) -> String {
+        if *self != OutputFormat::Table || !io::stdout().is_terminal() {
+            return self.render_tasks(tasks);
+        }
+
+        table(tasks, cli_manager::terminal_width(), colors_enabled())
+    }
+
+    /// Single task is printed as an object instead of array of objects
+    pub fn render_task(&self, task: &Task) -> String {
+        match self {
+            OutputFormat::Json => format!("{}\n", json_object(task)),
+            _ => self.render_tasks(std::slice::from_ref(task)),
+        }
+    }
+}
+
+fn status_name(status: &TaskStatus) -> &'static str {
+    match status {
+        TaskStatus::Completed(_) => "completed",
+        TaskStatus::Todo => "todo",
+        TaskStatus::Postponed(_) => "postponed",
+        TaskStatus::Expired(_) => "expired",
+        TaskStatus::Aborted(_) => "aborted",
+    }
+}
+
+fn status_date(status: &TaskStatus) -> Option<DateTime<Local>> {
+    match status {
+        TaskStatus::Completed(date)
+        | TaskStatus::Postponed(date)
+        | TaskStatus::Expired(date)
+        | TaskStatus::Aborted(date) => Some(*date),
+        TaskStatus::Todo => None,
+    }
+}
+
+fn rfc3339(date: DateTime<Local>) -> String {
+    date.to_rfc3339_opts(SecondsFormat::Secs, false)
+}
+
+/// Values of the task in order of FIELDS, None stands for missing value
+fn field_values(task: &Task) -> Vec<Option<String>> {
+    vec![
+        Some(task.label.clone()),
+        Some(task.id().to_string()),
+        Some(task.unquoted_thing().to_string()),
+        Some(status_name(&task.status).to_string()),
+        status_date(&task.status).map(rfc3339),
+        Some(rfc3339(task.deadline.date)),
+        task.created.map(rfc3339),
+        task.modified.map(rfc3339),
+        Some(task.note.clone()).filter(|note| !note.is_empty()),
+        Some(
+            task.annotations
+                .iter()
+                .map(|annotation| format!("{} {}", rfc3339(annotation.at), annotation.text))
+                .collect::<Vec<_>>()
+                .join("\n"),
+        )
+        .filter(|annotations| !annotations.is_empty()),
+        Some(task.attachments.join("\n")).filter(|attachments| !attachments.is_empty()),
+        task.estimate
+            .map(|estimate| estimate.num_minutes().to_string()),
+        (!task.intervals.is_empty()).then(|| timer::total(task).num_minutes().to_string()),
+    ]
+}
+
+fn json_string(value: &str) -> String {
+    let mut escaped = String::with_capacity(value.len() + 2);
+    escaped.push('"');
+
+    for c in value.chars() {
+        match c {
+            '"' => escaped.push_str("\\\""),
+            '\\' => escaped.push_str("\\\\"),
+            '\n' => escaped.push_str("\\n"),
+            '\r' => escaped.push_str("\\r"),
+            '\t' => escaped.push_str("\\t"),
+            c if (c as u32) < 0x20 => escaped.push_str(&format!("\\u{:04x}", c as u32)),
+            c => escaped.push(c),
+        }
+    }
+
+    escaped.push('"');
+    escaped
+}
+
+fn json_object(task: &Task) -> String {
+    let members = FIELDS
+        .iter()
+        .zip(field_values(task))
+        .map(|(field, value)| {
+            let value = match (field, value) {
+                (_, None) => "null".to_string(),
+                (&"id" | &"estimate" | &"tracked", Some(number)) => number,
+                (_, Some(value)) => json_string(&value),
+            };
+            format!("{}: {value}", json_string(field))
+        })
+        .collect::<Vec<_>>();
+
+    format!("{{{}}}", members.join(", "))
+}
+
+fn csv_value(value: &str) -> String {
+    if value.contains([',', '"', '\n', '\r']) {
+        format!("\"{}\"", value.replace('"', "\"\""))
+    } else {
+        value.to_string()
+    }
+}
+
+fn tsv_value(value: &str) -> String {
+    value
+        .replace('\\', "\\\\")
+        .replace('\t', "\\t")
+        .replace('\n', "\\n")
+        .replace('\r', "\\r")
+}
+
+fn separated_values(tasks: &[Task], separator: &str, escape: fn(&str) -> String) -> String {
+    let mut output = format!("{}\n", FIELDS.join(separator));
+
+    for task in tasks {
+        let row = field_values(task)
+            .into_iter()
+            .map(|value| value.map(|value| escape(&value)).unwrap_or_default())
+            .collect::<Vec<_>>();
+
+        output.push_str(&format!("{}\n", row.join(separator)));
+    }
+
+    output
+}
+
+/// Tasks that stand out in the table
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub(crate) enum Highlight {
+    Expired,
+    DueToday,
+    Inactive,
+}
+
+impl Highlight {
+    pub(crate) fn of(task: &Task, now: &DateTime<Local>) -> Option<Self> {
+        match task.status {
+            TaskStatus::Expired(_) => Some(Highlight::Expired),
+            TaskStatus::Completed(_) | TaskStatus::Aborted(_) => Some(Highlight::Inactive),
+            _ if task.effective_date().date_naive() == now.date_naive() => {
+                Some(Highlight::DueToday)
+            }
+            _ => None,
+        }
+    }
+
+    fn ansi_code(&self) -> &'static str {
+        match self {
+            Highlight::Expired => "\x1b[31m",
+            Highlight::DueToday => "\x1b[33m",
+            Highlight::Inactive => "\x1b[2m",
+        }
+    }
+}
+
+const ANSI_RESET: &str = "\x1b[0m";
+const COLUMN_SEPARATOR: &str = "  ";
+const MIN_THING_WIDTH: usize = 10;
+
+/// Colors are used only on the terminal and can be turned off by NO_COLOR, see https://no-color.org
+pub(crate) fn colors_enabled() -> bool {
+    std::env::var_os("NO_COLOR").is_none_or(|value| value.is_empty()) && io::stdout().is_terminal()
+}
+
+/// Cuts the text to given number of characters, the last one is replaced by ellipsis
+fn truncate(text: &str, max_width: usize) -> String {
+    if text.chars().count() <= max_width {
+        return text.to_string();
+    }
+
+    let mut truncated = text
+        .chars()
+        .take(max_width.saturating_sub(1))
+        .collect::<String>();
+    truncated.push('…');
+    truncated
+}
+
+/// Thing is truncated so the row fits the max_width
+fn table(tasks: &[Task], max_width: Option<usize>, color: bool) -> String {
+    const THING: usize = 1;
+
+    let header = ["ID", "Thing", "Status", "Deadline"].map(String::from);
+    let now = DateTime::date_now();
+
+    let mut rows = tasks
+        .iter()
+        .map(|task| {
+            [
+                task.id().to_string(),
+                task.unquoted_thing().to_string(),
+                task.status.to_string(),
+                DateTime::date_user_formating(task.deadline.date),
+            ]
+        })
+        .collect::<Vec<_>>();
+
+    let column_widths = |rows: &[[String; 4]]| {
+        let mut widths = header.clone().map(|column| column.chars().count());
+        for row in rows {
+            for (width, column) in widths.iter_mut().zip(row) {
+                *width = (*width).max(column.chars().count());
+            }
+        }
+        widths
+    };
+
+    if let Some(max_width) = max_width {
+        let widths = column_widths(&rows);
+        let other_columns = widths.iter().sum::<usize>() - widths[THING]
+            + COLUMN_SEPARATOR.len() * (widths.len() - 1);
+        let max_thing_width = max_width.saturating_sub(other_columns).max(MIN_THING_WIDTH);
+
+        for row in rows.iter_mut() {
+            row[THING] = truncate(&row[THING], max_thing_width);
+        }
+    }
+
+    let widths = column_widths(&rows);
+
+    let highlights = std::iter::once(None).chain(
+        tasks
+            .iter()
+            .map(|task| Highlight::of(task, &now).filter(|_| color)),
+    );
+
+    std::iter::once(&header)
+        .chain(rows.iter())
+        .zip(highlights)
+        .map(|(row, highlight)| {
+            let line = row
+                .iter()
+                .zip(widths)
+                .map(|(column, width)| format!("{column:<width$}"))
+                .collect::<Vec<_>>()
+                .join(COLUMN_SEPARATOR);
+            let line = line.trim_end();
+
+            match highlight {
+                Some(highlight) => format!("{}{line}{ANSI_RESET}\n", highlight.ansi_code()),
+                None => format!("{line}\n"),
+            }
+        })
+        .collect()
+}
@@ -0,0 +1,162 @@
+use crate::{commands, tasks_file_manager, Task};
+use rustyline::completion::Completer;
+use rustyline::error::ReadlineError;
+use rustyline::highlight::Highlighter;
+use rustyline::hint::Hinter;
+use rustyline::history::FileHistory;
+use rustyline::validate::Validator;
+use rustyline::{CompletionType, Config, Context, Editor, Helper};
+use std::io::{self, Write};
+use std::path::PathBuf;
+
+const HISTORY_FILE: &str = "prompt_history.txt";
+const MAX_HISTORY_SIZE: usize = 1000;
+
+/// Completes the interactive input the same way as shell completion scripts
+struct PromptHelper {
+    // tasks as they were when the line started, for completion of task ids and tags
+    tasks: Vec<Task>,
+}
+
+impl Completer for PromptHelper {
+    type Candidate = String;
+
+    fn complete(
+        &self,
+        line: &str,
+        pos: usize,
+        _ctx: &Context<'_>,
+    ) -> rustyline::Result<(usize, Vec<String>)> {
+        let line = &line[..pos];
+        let start = line
+            .rfind(char::is_whitespace)
+            .map(|idx| idx + 1)
+            .unwrap_or(0);
+
+        let mut words = line[..start]
+            .split_whitespace()
+            .map(String::from)
+            .collect::<Vec<_>>();
+        words.push(line[start..].to_string());
+
+        Ok((start, commands::complete(&words, &self.tasks)))
+    }
+}
+
+impl Hinter for PromptHelper {
+    type Hint = String;
+}
+
+impl Highlighter for PromptHelper {}
+
+impl Validator for PromptHelper {}
+
+impl Helper for PromptHelper {}
+
+/// Line editing, Ctrl-R search and tab completion, history is kept across sessions in prompt_history.txt
+pub struct Prompt {
+    // None if the terminal could not be set up, input is read as plain lines then
+    editor: Option<Editor<PromptHelper, FileHistory>>,
+    history_path: Option<PathBuf>,
+}
+
+impl Prompt {
+    pub fn new() -> Self {
+        let config = Config::builder()
+            .max_history_size(MAX_HISTORY_SIZE)
+            .and_then(|config| config.history_ignore_dups(true))
+            .map(|config| {
+                config
+                    .history_ignore_space(true)
+                    .completion_type(CompletionType::List)
+                    .build()
+            });
+
+        let editor = config
+            .and_then(Editor::with_config)
+            .inspect_err(|err| eprintln!("Line editing is not available: {err}"))
+            .ok()
+            .map(|mut editor| {
+                editor.set_helper(Some(PromptHelper { tasks: vec![] }));
+                editor
+            });
+
+        let history_path = tasks_file_manager::make_file_path_in_working_dir(HISTORY_FILE)
+            .inspect_err(|err| eprintln!("{err}"))
+            .ok();
+
+        let mut prompt = Prompt {
+            editor,
+            history_path,
+        };
+
+        if let (Some(editor), Some(path)) = (&mut prompt.editor, &prompt.history_path) {
+            // history file does not exist on the first run
+            if path.exists() {
+                if let Err(err) = editor.load_history(path) {
+                    eprintln!("Could not load the prompt history: {err}");
+                }
+            }
+        }
+
+        prompt
+    }
+
+    /// Reads next action, None if the input has ended (Ctrl-D)
+    pub fn read_line(&mut self, prompt: &str, tasks: &[Task]) -> Option<String> {
+        let editor = match &mut self.editor {
+            Some(editor) => editor,
+            None => return Self::read_plain_line(prompt),
+        };
+
+        if let Some(helper) = editor.helper_mut() {
+            helper.tasks = tasks.to_vec();
+        }
+
+        loop {
+            match editor.readline(prompt) {
+                Ok(line) => {
+                    if !line.trim().is_empty() {
+                        self.add_to_history(&line);
+                    }
+                    return Some(line);
+                }
+                // Ctrl-C discards the line
+                Err(ReadlineError::Interrupted) => continue,
+                Err(ReadlineError::Eof) => return None,
+                Err(err) => {
+                    eprintln!("Could not read the input: {err}");
+                    return None;
+                }
+            }
+        }
+    }
+
+    fn add_to_history(&mut self, line: &str) {
+        let editor = match &mut self.editor {
+            Some(editor) => editor,
+            None => return,
+        };
+
+        if let Err(err) = editor.add_history_entry(line) {
+            eprintln!("{err}");
+        }
+
+        if let Some(path) = &self.history_path {
+            if let Err(err) = editor.save_history(path) {
+                eprintln!("Could not save the prompt history: {err}");
+            }
+        }
+    }
+
+    fn read_plain_line(prompt: &str) -> Option<String> {
+        print!("{prompt}");
+        io::stdout().flush().expect("Failed to flush stdout");
+
+        let mut line = String::new();
+        match io::stdin().read_line(&mut line) {
+            Ok(0) | Err(_) => None,
+            Ok(_) => Some(line.trim_end_matches(['\n', '\r']).to_string()),
+        }
+    }
+}
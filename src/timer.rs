@@ -0,0 +1,308 @@
+use crate::history::{self, ChangeKind};
+use crate::{tasks_file_manager, DateTimeFormatter, HistoryEntry, Task};
+use chrono::{DateTime, Duration, Local, SecondsFormat};
+use std::collections::HashMap;
+
+/// Time worked on the task, the interval of the running timer has no end
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct Interval {
+    pub start: DateTime<Local>,
+    pub end: Option<DateTime<Local>>,
+}
+
+impl Interval {
+    fn duration(&self, now: DateTime<Local>) -> Duration {
+        self.end.unwrap_or(now) - self.start
+    }
+}
+
+/// Starts the timer of the task, timer running on another task is stopped first
+pub fn start(
+    tasks: &mut [Task],
+    tasks_history: &mut HashMap<String, Vec<HistoryEntry>>,
+    task_label_number: &str,
+) -> Result<(), String> {
+    let task_labeled_by = Task::find_by_label_number(tasks, task_label_number)?
+        .label
+        .clone();
+
+    if let Some(task) = running(tasks) {
+        if task.label == task_labeled_by {
+            return Err(format!("Timer of {task_labeled_by} is already running"));
+        }
+    }
+
+    stop_running(tasks, tasks_history);
+
+    let task = Task::find_by_label_number(tasks, task_label_number)?;
+    let prev_task = task.clone();
+
+    task.intervals.push(Interval {
+        start: Task::timestamp(),
+        end: None,
+    });
+
+    history::record(tasks_history, ChangeKind::Track, prev_task, task);
+
+    println!("Started timer of {task_labeled_by}");
+
+    tasks_file_manager::save(tasks, tasks_history);
+
+    Ok(())
+}
+
+/// Stops the running timer, whichever task it belongs to
+pub fn stop(
+    tasks: &mut [Task],
+    tasks_history: &mut HashMap<String, Vec<HistoryEntry>>,
+) -> Result<(), String> {
+    if !stop_running(tasks, tasks_history) {
+        return Err("No timer is running".to_string());
+    }
+
+    tasks_file_manager::save(tasks, tasks_history);
+
+    Ok(())
+}
+
+fn stop_running(
+    tasks: &mut [Task],
+    tasks_history: &mut HashMap<String, Vec<HistoryEntry>>,
+) -> bool {
+    let task = match tasks.iter_mut().find(|task| is_running(task)) {
+        Some(task) => task,
+        None => return false,
+    };
+    let prev_task = task.clone();
+    let now = Task::timestamp();
+
+    if let Some(interval) = task
+        .intervals
+        .iter_mut()
+        .find(|interval| interval.end.is_none())
+    {
+        interval.end = Some(now);
+        println!(
+            "Stopped timer of {} after {}",
+            task.label,
+            format_duration(interval.duration(now))
+        );
+    }
+
+    history::record(tasks_history, ChangeKind::Track, prev_task, task);
+
+    true
+}
+
+/// Adds time worked on the task without running the timer, e.g. 1h30m, the interval ends now
+pub fn log_time(
+    tasks: &mut [Task],
+    tasks_history: &mut HashMap<String, Vec<HistoryEntry>>,
+    task_label_number: &str,
+    duration: &str,
+) -> Result<(), String> {
+    let duration = parse_duration(duration)?;
+
+    let task = Task::find_by_label_number(tasks, task_label_number)?;
+    let prev_task = task.clone();
+    let now = Task::timestamp();
+    let start = now
+        .checked_sub_signed(duration)
+        .ok_or(format!("Could not log {}", format_duration(duration)))?;
+
+    task.intervals.push(Interval {
+        start,
+        end: Some(now),
+    });
+
+    history::record(tasks_history, ChangeKind::Track, prev_task, task);
+
+    println!(
+        "Logged {} on {}, {} in total",
+        format_duration(duration),
+        task.label,
+        format_duration(total(task))
+    );
+
+    tasks_file_manager::save(tasks, tasks_history);
+
+    Ok(())
+}
+
+fn is_running(task: &Task) -> bool {
+    task.intervals.iter().any(|interval| interval.end.is_none())
+}
+
+/// Task which timer is running, only one timer runs at a time
+pub fn running(tasks: &[Task]) -> Option<&Task> {
+    tasks.iter().find(|task| is_running(task))
+}
+
+/// Time worked on the task, including the running timer
+pub fn total(task: &Task) -> Duration {
+    let now = DateTime::date_now();
+
+    task.intervals
+        .iter()
+        .map(|interval| interval.duration(now))
+        .fold(Duration::zero(), |total, duration| total + duration)
+}
+
+/// Time worked on the task between the dates, intervals crossing the window are clipped to it
+pub fn total_between(
+    task: &Task,
+    from: Option<DateTime<Local>>,
+    to: Option<DateTime<Local>>,
+) -> Duration {
+    let now = DateTime::date_now();
+
+    task.intervals
+        .iter()
+        .map(|interval| {
+            let start = from.map_or(interval.start, |from| interval.start.max(from));
+            let end = to.map_or(interval.end.unwrap_or(now), |to| {
+                interval.end.unwrap_or(now).min(to)
+            });
+            (end - start).max(Duration::zero())
+        })
+        .fold(Duration::zero(), |total, duration| total + duration)
+}
+
+/// Time of finished intervals, the running timer is only marked so the history does not change with time
+pub fn summary(task: &Task) -> String {
+    let finished = task
+        .intervals
+        .iter()
+        .filter_map(|interval| Some(interval.end? - interval.start))
+        .fold(Duration::zero(), |total, duration| total + duration);
+
+    if is_running(task) {
+        format!("{} + running", format_duration(finished))
+    } else {
+        format_duration(finished)
+    }
+}
+
+/// Prompt of the interactive mode shows the running timer, e.g. [Task 5 0h12m] prompt:
+pub fn prompt(tasks: &[Task]) -> String {
+    match running(tasks) {
+        Some(task) => format!("[{} {}] prompt: ", task.label, format_duration(total(task))),
+        None => "prompt: ".to_string(),
+    }
+}
+
+/// Tracked time printed under the task, empty if no time was tracked
+pub fn describe(task: &Task) -> String {
+    if task.intervals.is_empty() {
+        return String::new();
+    }
+
+    let running = task
+        .intervals
+        .iter()
+        .find(|interval| interval.end.is_none())
+        .map(|interval| {
+            format!(
+                ", running since {}",
+                DateTime::date_user_formating(interval.start)
+            )
+        })
+        .unwrap_or_default();
+
+    format!(
+        "Time tracked: {} in {} intervals{running}\n",
+        format_duration(total(task)),
+        task.intervals.len()
+    )
+}
+
+/// e.g. 1h30m, 45m, 2h, 1h 30m
+pub fn parse_duration(input: &str) -> Result<Duration, String> {
+    let invalid = || format!("Invalid duration {input}, expected e.g. 1h30m, 45m or 2h");
+    let input = input.replace(' ', "").to_lowercase();
+
+    let mut duration = Duration::zero();
+    let mut number = String::new();
+
+    for c in input.chars() {
+        match c {
+            '0'..='9' => number.push(c),
+            'h' | 'm' => {
+                let value = number.parse::<i64>().map_err(|_| invalid())?;
+                number.clear();
+
+                // Duration::hours and Duration::minutes panic on overflow
+                let seconds = match c {
+                    'h' => value.checked_mul(60 * 60),
+                    _ => value.checked_mul(60),
+                }
+                .filter(|seconds| *seconds <= Duration::max_value().num_seconds())
+                .ok_or_else(invalid)?;
+
+                duration = duration
+                    .checked_add(&Duration::seconds(seconds))
+                    .ok_or_else(invalid)?;
+            }
+            _ => return Err(invalid()),
+        }
+    }
+
+    if !number.is_empty() || duration <= Duration::zero() {
+        return Err(invalid());
+    }
+
+    Ok(duration)
+}
+
+pub fn format_duration(duration: Duration) -> String {
+    format!(
+        "{}h{:02}m",
+        duration.num_hours(),
+        duration.num_minutes() % 60
+    )
+}
+
+// Intervals of the task line are separated by semicolons, the end of the running one is empty:
+// intervals: 2026-10-19T10:00:00+02:00/2026-10-19T11:30:00+02:00;2026-10-19T13:00:00+02:00/
+pub fn format_field(task: &Task) -> String {
+    if task.intervals.is_empty() {
+        return String::new();
+    }
+
+    let rfc3339 = |date: DateTime<Local>| date.to_rfc3339_opts(SecondsFormat::Secs, false);
+
+    let intervals = task
+        .intervals
+        .iter()
+        .map(|interval| {
+            format!(
+                "{}/{}",
+                rfc3339(interval.start),
+                interval.end.map(rfc3339).unwrap_or_default()
+            )
+        })
+        .collect::<Vec<_>>()
+        .join(";");
+
+    format!(", intervals: {intervals}")
+}
+
+pub fn parse_field(intervals: &str) -> Vec<Interval> {
+    let parse = |date: &str| {
+        DateTime::parse_from_rfc3339(date)
+            .ok()
+            .map(|date| date.with_timezone(&Local))
+    };
+
+    intervals
+        .split(';')
+        .filter_map(|interval| {
+            let (start, end) = interval.trim().split_once('/')?;
+
+            Some(Interval {
+                start: parse(start)?,
+                end: parse(end),
+            })
+        })
+        .collect()
+}
@@ -0,0 +1,293 @@
+use crate::history::{self, ChangeKind, HistoryEntry};
+use crate::tasks_file_manager::{self, format_task_line, parse_task_line};
+use crate::{DateTimeFormatter, Task};
+use chrono::{DateTime, Duration, Local, SecondsFormat};
+use std::collections::HashMap;
+use std::error::Error;
+use std::fs::{self, OpenOptions};
+use std::io::Write;
+
+const TRASH_FILE: &str = "tasks_trash.txt";
+
+/// Tombstone of deleted task, the task is kept in its final state together with its history
+#[derive(Debug, Clone)]
+struct Trashed {
+    deleted_at: DateTime<Local>,
+    task: Task,
+    history: Vec<HistoryEntry>,
+}
+
+/// Deleted tasks are kept until the trash is emptied, unless RUTODO_TRASH_DAYS limits the number of days
+fn retention() -> Option<Duration> {
+    std::env::var("RUTODO_TRASH_DAYS")
+        .ok()
+        .and_then(|days| days.trim().parse::<i64>().ok())
+        .filter(|days| *days >= 0)
+        .map(Duration::days)
+}
+
+/// Moves deleted tasks with their history into the trash, the deletion is recorded in the history
+pub fn put(deleted: Vec<(Task, Vec<HistoryEntry>)>) -> Result<(), Box<dyn Error>> {
+    if deleted.is_empty() {
+        return Ok(());
+    }
+
+    let mut trashed = load()?;
+    let deleted_at = DateTime::date_now();
+
+    trashed.extend(deleted.into_iter().map(|(task, mut history)| {
+        history.push(HistoryEntry::new(
+            ChangeKind::Delete,
+            task.clone(),
+            task.clone(),
+        ));
+
+        Trashed {
+            deleted_at,
+            task,
+            history,
+        }
+    }));
+
+    save(&trashed)
+}
+
+pub fn list() -> Result<(), String> {
+    let trashed = load().map_err(|err| err.to_string())?;
+
+    if trashed.is_empty() {
+        println!("Trash is empty");
+        return Ok(());
+    }
+
+    for entry in &trashed {
+        // tasks deleted before the deletion was recorded in the history have no user
+        let deleted_by = entry
+            .history
+            .last()
+            .filter(|change| change.kind == ChangeKind::Delete)
+            .and_then(|change| change.user.as_deref())
+            .map(|user| format!(" by {user}"))
+            .unwrap_or_default();
+
+        println!(
+            "{}Deleted: {}{deleted_by}\nHistory: {} changes\n",
+            entry.task,
+            DateTime::date_user_formating(entry.deleted_at),
+            entry.history.len()
+        );
+    }
+
+    match retention() {
+        Some(retention) => println!("Deleted tasks are kept for {} days", retention.num_days()),
+        None => println!("Deleted tasks are kept until the trash is emptied"),
+    }
+
+    Ok(())
+}
+
+/// Puts the most recently deleted task with given id back, under the first available id if its label is taken
+pub fn restore(
+    tasks: &mut Vec<Task>,
+    tasks_history: &mut HashMap<String, Vec<HistoryEntry>>,
+    task_label_number: &str,
+) -> Result<(), String> {
+    let task_labeled_by = format!("Task {task_label_number}");
+    let mut trashed = load().map_err(|err| err.to_string())?;
+
+    let idx = trashed
+        .iter()
+        .rposition(|entry| entry.task.label == task_labeled_by)
+        .ok_or(format!("{task_labeled_by} is not in the trash"))?;
+
+    let Trashed {
+        mut task,
+        mut history,
+        ..
+    } = trashed.remove(idx);
+    let deleted_task = task.clone();
+
+    // task could be brought back by undo already
+    if tasks.contains(&task) {
+        println!("{task_labeled_by} is not deleted anymore, removed it from the trash");
+    } else {
+        if tasks.iter().any(|x| x.label == task.label) {
+            let mut all_ids = Task::get_all_ids(tasks);
+            let label = match Task::find_available_ids(&mut all_ids).first() {
+                Some(id) => format!("Task {id}"),
+                None => format!("Task {}", all_ids.iter().max().unwrap_or(&0) + 1),
+            };
+
+            task.label = label.clone();
+            for entry in history.iter_mut() {
+                entry.before.label = label.clone();
+                entry.after.label = label.clone();
+            }
+        }
+
+        println!("Restored task:\n{task}");
+
+        history.push(HistoryEntry::new(
+            ChangeKind::Restore,
+            deleted_task,
+            task.clone(),
+        ));
+        tasks_history.insert(task.label.clone(), history);
+        tasks.push(task);
+
+        tasks_file_manager::save(tasks, tasks_history);
+    }
+
+    save(&trashed).map_err(|err| err.to_string())
+}
+
+/// Removes the tombstone of the task brought back by undo or redo, the most recent one if it was deleted more times
+pub fn take(task: &Task) -> Result<(), String> {
+    let mut trashed = load().map_err(|err| err.to_string())?;
+
+    // tasks saved before they had uid are matched by their label
+    let idx = trashed.iter().rposition(|entry| {
+        if task.uid.is_empty() {
+            entry.task.label == task.label
+        } else {
+            entry.task.uid == task.uid
+        }
+    });
+
+    if let Some(idx) = idx {
+        trashed.remove(idx);
+        save(&trashed).map_err(|err| err.to_string())?;
+    }
+
+    Ok(())
+}
+
+/// Puts the task back into the trash when its restoration is undone, as it was before it was restored
+pub fn put_back(task: Task, mut history: Vec<HistoryEntry>) -> Result<(), String> {
+    let mut trashed = load().map_err(|err| err.to_string())?;
+
+    let task = match history.last() {
+        Some(entry) if entry.kind == ChangeKind::Restore => {
+            let deleted_task = entry.before.clone();
+            history.pop();
+
+            // restored task could get another label
+            for entry in history.iter_mut() {
+                entry.before.label = deleted_task.label.clone();
+                entry.after.label = deleted_task.label.clone();
+            }
+
+            deleted_task
+        }
+        _ => task,
+    };
+
+    let deleted_at = history
+        .last()
+        .filter(|entry| entry.kind == ChangeKind::Delete)
+        .and_then(|entry| entry.at)
+        .unwrap_or(DateTime::date_now());
+
+    trashed.push(Trashed {
+        deleted_at,
+        task,
+        history,
+    });
+
+    save(&trashed).map_err(|err| err.to_string())
+}
+
+pub fn empty() -> Result<(), String> {
+    save(&[]).map_err(|err| err.to_string())?;
+    println!("Trash emptied");
+
+    Ok(())
+}
+
+/// History of every deleted task, the deletion is its last entry
+pub fn histories() -> Result<Vec<Vec<HistoryEntry>>, String> {
+    let trashed = load().map_err(|err| err.to_string())?;
+
+    Ok(trashed.into_iter().map(|entry| entry.history).collect())
+}
+
+// Trash file consists of deleted tasks followed by their history:
+// Deleted { at: 2023-06-10T12:30:00+02:00 }
+// task: Task { thing: "buy milk", status: Todo, label: Task 4, deadline: Deadline { date: 2023-06-10T12:30:00+02:00 } }
+// history: Change { ... } before: Task { ... } after: Task { ... }
+fn save(trashed: &[Trashed]) -> Result<(), Box<dyn Error>> {
+    let file_path = tasks_file_manager::make_file_path_in_working_dir(TRASH_FILE)?;
+
+    let mut file = OpenOptions::new()
+        .write(true)
+        .truncate(true)
+        .create(true)
+        .open(file_path)?;
+
+    for entry in trashed {
+        writeln!(
+            file,
+            "Deleted {{ at: {} }}",
+            entry.deleted_at.to_rfc3339_opts(SecondsFormat::Secs, false)
+        )?;
+        writeln!(file, "task: {}", format_task_line(&entry.task))?;
+
+        for history_entry in &entry.history {
+            writeln!(
+                file,
+                "history: {}",
+                history::format_entry_line(history_entry)
+            )?;
+        }
+    }
+
+    Ok(())
+}
+
+/// Tasks deleted longer than the retention period ago are left out
+fn load() -> Result<Vec<Trashed>, Box<dyn Error>> {
+    let file_path = tasks_file_manager::make_file_path_in_working_dir(TRASH_FILE)?;
+
+    if !file_path.exists() {
+        return Ok(vec![]);
+    }
+
+    let mut trashed: Vec<Trashed> = vec![];
+    let mut deleted_at = None;
+
+    for line in fs::read_to_string(file_path)?.lines() {
+        if let Some(date) = line
+            .strip_prefix("Deleted { at: ")
+            .and_then(|line| line.strip_suffix(" }"))
+        {
+            deleted_at = Some(DateTime::parse_from_rfc3339(date)?.with_timezone(&Local));
+            continue;
+        }
+
+        match line.split_once(": ") {
+            Some(("task", task)) => trashed.push(Trashed {
+                deleted_at: deleted_at
+                    .ok_or(format!("Task without deletion date in the trash: {line}"))?,
+                task: parse_task_line(task),
+                history: vec![],
+            }),
+            Some(("history", entry)) => trashed
+                .last_mut()
+                .ok_or(format!("History without task in the trash: {line}"))?
+                .history
+                .push(history::parse_entry_line(entry)?),
+            _ => return Err(format!("Invalid line in the trash: {line}").into()),
+        }
+    }
+
+    for entry in trashed.iter_mut() {
+        history::link_legacy(&mut entry.history, Some(&entry.task));
+    }
+
+    if let Some(retention) = retention() {
+        let expired_before = DateTime::date_now() - retention;
+        trashed.retain(|entry| entry.deleted_at >= expired_before);
+    }
+
+    Ok(trashed)
+}
@@ -0,0 +1,676 @@
+use crate::cli_manager::{self, Switches};
+use crate::history::{self, ChangeKind};
+use crate::output_format::{self, Highlight};
+use crate::{
+    attachments, estimates, journal, notes, tasks_file_manager, timer, DateTimeFormatter, Deadline,
+    HistoryEntry, OutputFormat, Task,
+};
+use chrono::DateTime;
+use ratatui::crossterm::event::{self, Event, KeyCode, KeyEvent, KeyEventKind, KeyModifiers};
+use ratatui::layout::{Constraint, Layout, Rect};
+use ratatui::style::{Style, Stylize};
+use ratatui::text::Line;
+use ratatui::widgets::{Block, Paragraph, Row, Table, TableState, Wrap};
+use ratatui::Frame;
+use std::collections::HashMap;
+use std::io::{self, IsTerminal};
+
+const KEYS_HELP: &str =
+    "j/k move  / filter  a add  e edit  c complete  p postpone  d delete  q quit";
+
+/// What the typed text is used for once it is submitted with Enter
+#[derive(Debug, Clone, PartialEq)]
+enum Input {
+    AddThing,
+    AddDeadline(String),
+    EditThing,
+    Postpone,
+    ConfirmDelete,
+}
+
+#[derive(Debug, Clone, PartialEq)]
+enum Mode {
+    Normal,
+    Filter,
+    Input(Input),
+}
+
+/// State of rutodo tui, driven only by handle_key and draw, so it runs against ratatui's TestBackend as well as the terminal
+pub struct App<'a> {
+    tasks: &'a mut Vec<Task>,
+    tasks_history: &'a mut HashMap<String, Vec<HistoryEntry>>,
+    // switches of show tasks, text without leading -- filters by --thing
+    filter: String,
+    // labels of tasks that passed the filter, in displayed order
+    visible: Vec<String>,
+    table_state: TableState,
+    mode: Mode,
+    input: String,
+    message: String,
+    pub quit: bool,
+}
+
+/// Runs the interface in the alternate screen until q is pressed
+pub fn run(
+    tasks: &mut Vec<Task>,
+    tasks_history: &mut HashMap<String, Vec<HistoryEntry>>,
+) -> Result<(), String> {
+    if !io::stdout().is_terminal() {
+        return Err("Command tui requires a terminal".to_string());
+    }
+
+    // notices about the storage are printed before the screen is taken over
+    tasks_file_manager::make_file_path_in_working_dir("tasks.txt")
+        .map_err(|err| err.to_string())?;
+
+    let mut app = App::new(tasks, tasks_history);
+    let mut terminal = ratatui::init();
+
+    let result = loop {
+        if let Err(err) = terminal.draw(|frame| app.draw(frame)) {
+            break Err(err.to_string());
+        }
+
+        match event::read() {
+            Ok(Event::Key(key)) if key.kind == KeyEventKind::Press => app.handle_key(key),
+            Ok(_) => (),
+            Err(err) => break Err(err.to_string()),
+        }
+
+        if app.quit {
+            break Ok(());
+        }
+    };
+
+    ratatui::restore();
+    result
+}
+
+impl<'a> App<'a> {
+    pub fn new(
+        tasks: &'a mut Vec<Task>,
+        tasks_history: &'a mut HashMap<String, Vec<HistoryEntry>>,
+    ) -> Self {
+        let mut app = App {
+            tasks,
+            tasks_history,
+            filter: String::new(),
+            visible: vec![],
+            table_state: TableState::default(),
+            mode: Mode::Normal,
+            input: String::new(),
+            message: String::new(),
+            quit: false,
+        };
+
+        app.refresh();
+        app.table_state
+            .select((!app.visible.is_empty()).then_some(0));
+        app
+    }
+
+    fn selected_task(&self) -> Option<&Task> {
+        let label = self.visible.get(self.table_state.selected()?)?;
+        self.tasks.iter().find(|task| task.label == *label)
+    }
+
+    /// Applies the filter again, previous result is kept if the filter is not valid yet
+    fn refresh(&mut self) {
+        match self.filtered() {
+            Ok(tasks) => {
+                self.visible = tasks.into_iter().map(|task| task.label).collect();
+                if self.mode == Mode::Filter {
+                    self.message.clear();
+                }
+            }
+            Err(err) => self.message = err,
+        }
+
+        let last = self.visible.len().checked_sub(1);
+        match (self.table_state.selected(), last) {
+            (_, None) => self.table_state.select(None),
+            (Some(selected), Some(last)) if selected > last => self.table_state.select(Some(last)),
+            (None, Some(_)) => self.table_state.select(Some(0)),
+            _ => (),
+        }
+    }
+
+    fn filtered(&self) -> Result<Vec<Task>, String> {
+        let filter = self.filter.trim();
+
+        if filter.is_empty() {
+            return Ok(self.tasks.to_vec());
+        }
+
+        let thing_filter;
+        let filter = if filter.starts_with("--") {
+            filter
+        } else {
+            thing_filter = format!("--thing {filter}");
+            &thing_filter
+        };
+
+        let switches: Option<Switches> = cli_manager::parse_to_switches_and_arguments(filter)?;
+
+        if switches
+            .iter()
+            .flatten()
+            .any(|(switch, _)| switch.trim_start_matches("--") == "redirect")
+        {
+            return Err("--redirect is not available in tui".to_string());
+        }
+
+        crate::commands::SHOW_TASKS.validate(&switches)?;
+        cli_manager::filter_tasks(self.tasks, switches, OutputFormat::Plain)
+    }
+
+    pub fn handle_key(&mut self, key: KeyEvent) {
+        if key.modifiers.contains(KeyModifiers::CONTROL) && key.code == KeyCode::Char('c') {
+            self.quit = true;
+            return;
+        }
+
+        match self.mode.clone() {
+            Mode::Normal => self.handle_normal_key(key),
+            Mode::Filter => match key.code {
+                KeyCode::Enter | KeyCode::Esc => {
+                    self.mode = Mode::Normal;
+                    self.message.clear();
+                }
+                KeyCode::Backspace => {
+                    self.filter.pop();
+                    self.refresh();
+                }
+                KeyCode::Char(c) => {
+                    self.filter.push(c);
+                    self.refresh();
+                }
+                _ => (),
+            },
+            Mode::Input(Input::ConfirmDelete) => {
+                self.mode = Mode::Normal;
+                match key.code {
+                    KeyCode::Char('y') | KeyCode::Char('Y') => self.delete_selected(),
+                    _ => self.message = "Nothing was changed".to_string(),
+                }
+            }
+            Mode::Input(input) => match key.code {
+                KeyCode::Esc => {
+                    self.mode = Mode::Normal;
+                    self.message.clear();
+                }
+                KeyCode::Enter => {
+                    self.mode = Mode::Normal;
+                    let value = std::mem::take(&mut self.input);
+                    self.submit(input, value);
+                }
+                KeyCode::Backspace => {
+                    self.input.pop();
+                }
+                KeyCode::Char(c) => self.input.push(c),
+                _ => (),
+            },
+        }
+    }
+
+    fn handle_normal_key(&mut self, key: KeyEvent) {
+        let selected = self.table_state.selected().unwrap_or(0);
+        let last = self.visible.len().saturating_sub(1);
+
+        match key.code {
+            KeyCode::Char('q') | KeyCode::Esc => self.quit = true,
+            KeyCode::Char('j') | KeyCode::Down => self.select(selected + 1),
+            KeyCode::Char('k') | KeyCode::Up => self.select(selected.saturating_sub(1)),
+            KeyCode::PageDown => self.select(selected + 10),
+            KeyCode::PageUp => self.select(selected.saturating_sub(10)),
+            KeyCode::Char('g') | KeyCode::Home => self.select(0),
+            KeyCode::Char('G') | KeyCode::End => self.select(last),
+            KeyCode::Char('/') => {
+                self.mode = Mode::Filter;
+                self.message.clear();
+            }
+            KeyCode::Char('a') => self.start_input(Input::AddThing, String::new()),
+            KeyCode::Char('e') => {
+                if let Some(task) = self.selected_task() {
+                    let thing = task.unquoted_thing().to_string();
+                    self.start_input(Input::EditThing, thing);
+                }
+            }
+            KeyCode::Char('p') if self.selected_task().is_some() => {
+                self.start_input(Input::Postpone, String::new());
+            }
+            KeyCode::Char('c') => {
+                self.update_selected(None, Some("completed"), "done");
+            }
+            KeyCode::Char('d') | KeyCode::Delete if self.selected_task().is_some() => {
+                self.mode = Mode::Input(Input::ConfirmDelete);
+            }
+            _ => (),
+        }
+    }
+
+    fn select(&mut self, idx: usize) {
+        if !self.visible.is_empty() {
+            self.table_state
+                .select(Some(idx.min(self.visible.len() - 1)));
+        }
+    }
+
+    fn start_input(&mut self, input: Input, initial: String) {
+        self.input = initial;
+        self.message.clear();
+        self.mode = Mode::Input(input);
+    }
+
+    fn submit(&mut self, input: Input, value: String) {
+        match input {
+            Input::AddThing if value.trim().is_empty() => {
+                self.message = "Task thing cannot be empty".to_string()
+            }
+            Input::AddThing => self.start_input(Input::AddDeadline(value), String::new()),
+            Input::AddDeadline(thing) => match Deadline::new(&value) {
+                Ok(deadline) => self.add(&thing, deadline),
+                Err(err) => {
+                    self.message = err.lines().last().unwrap_or_default().to_string();
+                    self.start_input(Input::AddDeadline(thing), value);
+                }
+            },
+            Input::EditThing => self.update_selected(Some(&value), None, "edit"),
+            Input::Postpone => {
+                self.update_selected(None, Some(&format!("postponed {value}")), "postpone")
+            }
+            Input::ConfirmDelete => self.delete_selected(),
+        }
+    }
+
+    /// Records the change in the journal, so it can be undone from the command line, and saves tasks
+    fn commit(&mut self, operation: &str, before: (Vec<Task>, HashMap<String, Vec<HistoryEntry>>)) {
+        if let Err(err) = journal::record(operation, &before, self.tasks, self.tasks_history) {
+            self.message = err.to_string();
+        }
+
+        if let Err(err) = tasks_file_manager::save_tasks(self.tasks) {
+            self.message = err.to_string();
+        }
+
+        if let Err(err) = tasks_file_manager::save_tasks_history(self.tasks, self.tasks_history) {
+            self.message = err.to_string();
+        }
+
+        self.refresh();
+    }
+
+    fn add(&mut self, thing: &str, deadline: Deadline) {
+        let before = (self.tasks.clone(), self.tasks_history.clone());
+
+        let mut task = Task::new_task(self.tasks, thing, deadline);
+        self.message = format!("{} added", task.label);
+        let label = task.label.clone();
+        history::record(self.tasks_history, ChangeKind::Add, task.clone(), &mut task);
+        self.tasks.push(task);
+
+        self.commit(&format!("add {thing}"), before);
+
+        if let Some(idx) = self.visible.iter().position(|x| *x == label) {
+            self.table_state.select(Some(idx));
+        }
+    }
+
+    fn update_selected(&mut self, thing: Option<&str>, status: Option<&str>, operation: &str) {
+        let label = match self.selected_task() {
+            Some(task) => task.label.clone(),
+            None => return,
+        };
+
+        let before = (self.tasks.clone(), self.tasks_history.clone());
+
+        let task = self
+            .tasks
+            .iter_mut()
+            .find(|task| task.label == label)
+            .expect("selected task exists");
+
+        match task.apply_update(self.tasks_history, thing, status, None) {
+            Ok(()) => {
+                self.message = format!("{label} updated");
+                self.commit(&format!("{operation} {}", task_number(&label)), before);
+            }
+            Err(err) => self.message = err,
+        }
+    }
+
+    fn delete_selected(&mut self) {
+        let label = match self.selected_task() {
+            Some(task) => task.label.clone(),
+            None => return,
+        };
+
+        let before = (self.tasks.clone(), self.tasks_history.clone());
+
+        let (deleted, kept) = std::mem::take(self.tasks)
+            .into_iter()
+            .partition(|task| task.label == label);
+        *self.tasks = kept;
+
+        self.message = match Task::trash_deleted(deleted, self.tasks_history) {
+            Ok(()) => format!("{label} moved to the trash"),
+            Err(err) => err,
+        };
+
+        self.commit(&format!("delete {}", task_number(&label)), before);
+    }
+
+    pub fn draw(&mut self, frame: &mut Frame) {
+        let [filter_area, main_area, status_area] = Layout::vertical([
+            Constraint::Length(3),
+            Constraint::Min(0),
+            Constraint::Length(1),
+        ])
+        .areas(frame.area());
+
+        let [list_area, side_area] =
+            Layout::horizontal([Constraint::Percentage(60), Constraint::Percentage(40)])
+                .areas(main_area);
+
+        let [detail_area, history_area] =
+            Layout::vertical([Constraint::Percentage(50), Constraint::Percentage(50)])
+                .areas(side_area);
+
+        self.draw_filter(frame, filter_area);
+        self.draw_list(frame, list_area);
+        self.draw_detail(frame, detail_area);
+        self.draw_history(frame, history_area);
+        self.draw_status(frame, status_area);
+    }
+
+    fn draw_filter(&self, frame: &mut Frame, area: Rect) {
+        let style = if self.mode == Mode::Filter {
+            Style::new().yellow()
+        } else {
+            Style::new()
+        };
+
+        let filter = Paragraph::new(self.filter.as_str()).block(
+            Block::bordered()
+                .title("Filter (switches of show tasks, plain text matches thing)")
+                .border_style(style),
+        );
+
+        frame.render_widget(filter, area);
+    }
+
+    fn draw_list(&mut self, frame: &mut Frame, area: Rect) {
+        let now = DateTime::date_now();
+        let color = output_format::colors_enabled();
+
+        let rows = self
+            .visible
+            .iter()
+            .filter_map(|label| self.tasks.iter().find(|task| task.label == *label))
+            .map(|task| {
+                let style = match Highlight::of(task, &now).filter(|_| color) {
+                    Some(Highlight::Expired) => Style::new().red(),
+                    Some(Highlight::DueToday) => Style::new().yellow(),
+                    Some(Highlight::Inactive) => Style::new().dim(),
+                    None => Style::new(),
+                };
+
+                Row::new([
+                    task.id().to_string(),
+                    task.unquoted_thing().to_string(),
+                    task.status.to_string(),
+                    DateTime::date_user_formating(task.deadline.date),
+                ])
+                .style(style)
+            })
+            .collect::<Vec<_>>();
+
+        let table = Table::new(
+            rows,
+            [
+                Constraint::Length(4),
+                Constraint::Fill(1),
+                Constraint::Length(28),
+                Constraint::Length(16),
+            ],
+        )
+        .header(Row::new(["ID", "Thing", "Status", "Deadline"]).bold())
+        .block(Block::bordered().title(format!(
+            "Tasks ({} of {})",
+            self.visible.len(),
+            self.tasks.len()
+        )))
+        .row_highlight_style(Style::new().reversed())
+        .highlight_symbol("> ");
+
+        frame.render_stateful_widget(table, area, &mut self.table_state);
+    }
+
+    fn draw_detail(&self, frame: &mut Frame, area: Rect) {
+        let text = match self.selected_task() {
+            Some(task) => {
+                let tags = task.tags();
+                let tags = if tags.is_empty() {
+                    String::new()
+                } else {
+                    format!("Tags: {}\n", tags.join(", "))
+                };
+
+                format!(
+                    "{task}{tags}{}{}{}{}",
+                    estimates::describe(task),
+                    timer::describe(task),
+                    notes::describe(task),
+                    attachments::describe(task)
+                )
+            }
+            None => "No task selected".to_string(),
+        };
+
+        let detail = Paragraph::new(text)
+            .block(Block::bordered().title("Detail"))
+            .wrap(Wrap { trim: false });
+
+        frame.render_widget(detail, area);
+    }
+
+    fn draw_history(&self, frame: &mut Frame, area: Rect) {
+        let lines = self
+            .selected_task()
+            .and_then(|task| self.tasks_history.get(&task.label))
+            .map(|entries| {
+                // the most recent change first
+                entries
+                    .iter()
+                    .enumerate()
+                    .rev()
+                    .map(|(idx, entry)| {
+                        Line::from(format!("{}. {}", idx + 1, entry.changelog_line()))
+                    })
+                    .collect::<Vec<_>>()
+            })
+            .unwrap_or_else(|| vec![Line::from("No previous versions")]);
+
+        let history = Paragraph::new(lines)
+            .block(Block::bordered().title("History"))
+            .wrap(Wrap { trim: false });
+
+        frame.render_widget(history, area);
+    }
+
+    fn draw_status(&self, frame: &mut Frame, area: Rect) {
+        let line = match &self.mode {
+            Mode::Input(input) => {
+                let label = match input {
+                    Input::AddThing => "Thing",
+                    Input::AddDeadline(_) => "Deadline",
+                    Input::EditThing => "New thing",
+                    Input::Postpone => "Postpone to (relative to the previous date)",
+                    Input::ConfirmDelete => "Delete selected task? (y/n)",
+                };
+
+                if *input == Input::ConfirmDelete {
+                    label.to_string()
+                } else if self.message.is_empty() {
+                    format!("{label}: {}", self.input)
+                } else {
+                    format!("{label}: {}  ({})", self.input, self.message)
+                }
+            }
+            Mode::Filter if self.message.is_empty() => "Enter/Esc to finish filtering".to_string(),
+            _ if !self.message.is_empty() => self.message.clone(),
+            _ => KEYS_HELP.to_string(),
+        };
+
+        frame.render_widget(Paragraph::new(line), area);
+    }
+}
+
+fn task_number(label: &str) -> &str {
+    label.trim_start_matches("Task ")
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::TaskStatus;
+    use ratatui::backend::TestBackend;
+    use ratatui::Terminal;
+    use std::fs;
+    use std::sync::atomic::{AtomicU32, Ordering};
+    use std::sync::Mutex;
+
+    // tests that touch files take the lock and store them in their own directory
+    static STORAGE: Mutex<()> = Mutex::new(());
+
+    fn in_temp_storage(test: impl FnOnce()) {
+        let _guard = STORAGE.lock().unwrap_or_else(|err| err.into_inner());
+
+        static COUNTER: AtomicU32 = AtomicU32::new(0);
+        let dir = std::env::temp_dir().join(format!(
+            "rutodo-tui-{}-{}",
+            std::process::id(),
+            COUNTER.fetch_add(1, Ordering::Relaxed)
+        ));
+        fs::create_dir_all(&dir).unwrap();
+
+        let result = std::panic::catch_unwind(std::panic::AssertUnwindSafe(|| {
+            tasks_file_manager::with_storage_dir(&dir, test)
+        }));
+
+        let _ = fs::remove_dir_all(&dir);
+
+        if let Err(panic) = result {
+            std::panic::resume_unwind(panic);
+        }
+    }
+
+    fn sample_tasks() -> Vec<Task> {
+        let mut tasks = vec![];
+
+        for thing in ["buy milk", "write report #work", "call bob"] {
+            let deadline = Deadline::new(&"tomorrow 12:30".to_string()).unwrap();
+            let task = Task::new_task(&tasks, thing, deadline);
+            tasks.push(task);
+        }
+
+        tasks
+    }
+
+    fn press(app: &mut App, keys: &str) {
+        for c in keys.chars() {
+            app.handle_key(KeyEvent::from(KeyCode::Char(c)));
+        }
+    }
+
+    fn screen(app: &mut App) -> String {
+        let mut terminal = Terminal::new(TestBackend::new(120, 30)).unwrap();
+        terminal.draw(|frame| app.draw(frame)).unwrap();
+
+        let buffer = terminal.backend().buffer();
+
+        buffer
+            .content()
+            .chunks(buffer.area.width as usize)
+            .map(|row| row.iter().map(|cell| cell.symbol()).collect::<String>())
+            .collect::<Vec<_>>()
+            .join("\n")
+    }
+
+    #[test]
+    fn filter_narrows_down_the_list() {
+        in_temp_storage(|| {
+            let mut tasks = sample_tasks();
+            let mut tasks_history = HashMap::new();
+            let mut app = App::new(&mut tasks, &mut tasks_history);
+
+            press(&mut app, "/buy");
+            let screen_text = screen(&mut app);
+
+            assert!(screen_text.contains("Tasks (1 of 3)"));
+            assert!(screen_text.contains("buy milk"));
+            assert!(!screen_text.contains("call bob"));
+
+            app.handle_key(KeyEvent::from(KeyCode::Enter));
+            press(&mut app, "/");
+            for _ in 0.."buy".len() {
+                app.handle_key(KeyEvent::from(KeyCode::Backspace));
+            }
+            press(&mut app, "--tag work");
+            let screen_text = screen(&mut app);
+
+            assert!(screen_text.contains("Tasks (1 of 3)"));
+            assert!(screen_text.contains("write report #work"));
+            assert!(!screen_text.contains("buy milk"));
+        });
+    }
+
+    #[test]
+    fn complete_marks_selected_task_as_completed() {
+        in_temp_storage(|| {
+            let mut tasks = sample_tasks();
+            let mut tasks_history = HashMap::new();
+            let mut app = App::new(&mut tasks, &mut tasks_history);
+
+            press(&mut app, "jc");
+            let screen_text = screen(&mut app);
+
+            assert!(screen_text.contains("Task 2 updated"));
+            assert!(screen_text.contains("Completed("));
+            drop(app);
+
+            assert!(matches!(tasks[1].status, TaskStatus::Completed(_)));
+            assert_eq!(tasks[0].status, TaskStatus::Todo);
+            assert_eq!(tasks_history["Task 2"].len(), 1);
+
+            let saved = tasks_file_manager::make_file_path_in_working_dir("tasks.txt").unwrap();
+            assert!(fs::read_to_string(saved).unwrap().contains("Completed("));
+        });
+    }
+
+    #[test]
+    fn delete_asks_for_confirmation() {
+        in_temp_storage(|| {
+            let mut tasks = sample_tasks();
+            let mut tasks_history = HashMap::new();
+            let mut app = App::new(&mut tasks, &mut tasks_history);
+
+            press(&mut app, "d");
+            assert!(screen(&mut app).contains("Delete selected task? (y/n)"));
+
+            press(&mut app, "n");
+            let screen_text = screen(&mut app);
+            assert!(screen_text.contains("Nothing was changed"));
+            assert!(screen_text.contains("Tasks (3 of 3)"));
+
+            press(&mut app, "dy");
+            let screen_text = screen(&mut app);
+            assert!(screen_text.contains("Task 1 moved to the trash"));
+            assert!(screen_text.contains("Tasks (2 of 2)"));
+            assert!(!screen_text.contains("buy milk"));
+            drop(app);
+
+            assert!(tasks.iter().all(|task| task.label != "Task 1"));
+        });
+    }
+}
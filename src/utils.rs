@@ -1,12 +1,13 @@
-use crate::tasks_file_manager::{parse_task_from_file, save_tasks};
+use crate::tasks_file_manager::{self, parse_task_from_file};
 use std::{collections::HashMap, error::Error, fs::OpenOptions, io::Read};
 
-use crate::Task;
+use crate::{HistoryEntry, Task};
 use std::path::PathBuf;
 
 // Accepts absolute file path
 pub fn parse_redirected_stream_of_show_tasks(
     tasks: &mut Vec<Task>,
+    tasks_history: &mut HashMap<String, Vec<HistoryEntry>>,
     file_path: PathBuf,
 ) -> Result<(), Box<dyn Error>> {
     println!(
@@ -93,13 +94,10 @@ pub fn parse_redirected_stream_of_show_tasks(
         println!("{instance_entries:?}");
 
         let task = parse_task_from_file(&mut instance_key_values);
-
-        tasks.push(task);
+        Task::import_task(tasks, tasks_history, task);
     }
 
-    if let Err(err) = save_tasks(tasks) {
-        eprintln!("Could not save the parsed tasks to file: {err}");
-    };
+    tasks_file_manager::save(tasks, tasks_history);
 
     Ok(())
 }